@@ -0,0 +1,115 @@
+//! Runs a horde through the physics solver headless, with and without enemy LOD, to see what
+//! taking far away enemies out of the solver buys us.
+//!
+//! Run with `cargo bench --bench enemy_lod`
+use std::time::{Duration, Instant};
+
+use avian2d::prelude::*;
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::{
+    colliders::ColliderTypes,
+    enemies::{
+        Enemy, EnemyKind, EnemyState,
+        lod::{SimplifiedPhysics, integrate_simplified_enemies, update_enemy_lod},
+    },
+    players::Player,
+};
+
+const ENEMY_COUNTS: [usize; 3] = [1_000, 2_500, 5_000];
+/// Most of the horde is off screen at any one time, so spread them out well past `LOD_FAR`
+const ARENA_HALF_SIZE: f32 = 3_000.0;
+const TICKS: u32 = 120;
+
+fn build_app(n_enemies: usize, lod: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        PhysicsPlugins::default().with_length_unit(1.0),
+    ))
+    .insert_resource(Gravity::ZERO)
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / 64.0,
+    )))
+    .add_systems(FixedUpdate, chase_origin);
+    if lod {
+        app.add_systems(
+            FixedUpdate,
+            (
+                update_enemy_lod::<(), ()>,
+                integrate_simplified_enemies::<()>,
+            )
+                .chain()
+                .after(chase_origin),
+        );
+    }
+
+    let world = app.world_mut();
+    world.spawn((
+        Player {
+            client: lightyear::prelude::PeerId::Local(0),
+        },
+        RigidBody::Kinematic,
+        Collider::capsule(20.0, 30.0),
+        CollisionLayers::new(ColliderTypes::Player, ColliderTypes::Enemy),
+    ));
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..n_enemies {
+        let pos = Vec2::new(
+            rng.random_range(-ARENA_HALF_SIZE..ARENA_HALF_SIZE),
+            rng.random_range(-ARENA_HALF_SIZE..ARENA_HALF_SIZE),
+        );
+        world.spawn((
+            Enemy {
+                kind: EnemyKind(0),
+                state: EnemyState::LookForTargets,
+            },
+            RigidBody::Dynamic,
+            Collider::circle(12.0),
+            CollisionLayers::new(ColliderTypes::Enemy, ColliderTypes::Player),
+            Position(pos),
+        ));
+    }
+    app
+}
+
+/// Keeps the horde moving in, so that the solver always has something to do
+fn chase_origin(mut q_enemy: Query<(&Position, &mut LinearVelocity), With<Enemy>>) {
+    for (pos, mut lv) in &mut q_enemy {
+        lv.0 = -pos.0.normalize_or_zero() * 60.0;
+    }
+}
+
+/// Average milliseconds per tick
+fn run(app: &mut App) -> f64 {
+    // Let everything settle in, so that the LOD has picked a side for every enemy
+    app.update();
+    app.update();
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        app.update();
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / TICKS as f64
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>12} {:>12} {:>12}",
+        "enemies", "full", "lod", "simplified"
+    );
+    for n in ENEMY_COUNTS {
+        let full = run(&mut build_app(n, false));
+        let mut lod_app = build_app(n, true);
+        let lod = run(&mut lod_app);
+        let simplified = lod_app
+            .world_mut()
+            .query_filtered::<(), With<SimplifiedPhysics>>()
+            .iter(lod_app.world())
+            .count();
+        println!(
+            "{:>8} {:>10.2}ms {:>10.2}ms {:>12}",
+            n, full, lod, simplified
+        );
+    }
+}
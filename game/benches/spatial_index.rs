@@ -0,0 +1,82 @@
+//! Compares the spatial index against scanning every entity, for the kinds of queries that
+//! enemies and weapons make every tick.
+//!
+//! Run with `cargo bench --bench spatial_index`
+use std::{hint::black_box, time::Instant};
+
+use bevy::{ecs::entity::Entity, math::Vec2};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::{colliders::ColliderTypes, spatial_index::SpatialIndex};
+
+const ENEMY_COUNTS: [usize; 4] = [500, 2_000, 5_000, 10_000];
+/// Roughly how many things are asking for the nearest enemy every tick
+const QUERIES: usize = 200;
+/// About how spread out the enemies get over the course of a match
+const ARENA_HALF_SIZE: f32 = 3_000.0;
+const RUNS: u32 = 20;
+
+fn random_pos(rng: &mut StdRng) -> Vec2 {
+    Vec2::new(
+        rng.random_range(-ARENA_HALF_SIZE..ARENA_HALF_SIZE),
+        rng.random_range(-ARENA_HALF_SIZE..ARENA_HALF_SIZE),
+    )
+}
+
+/// Average microseconds per run
+fn time(mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    start.elapsed().as_secs_f64() * 1_000_000.0 / RUNS as f64
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    println!(
+        "{:>8} {:>12} {:>14} {:>14} {:>14}",
+        "enemies", "rebuild", "nearest(scan)", "nearest(index)", "radius(index)"
+    );
+    for n in ENEMY_COUNTS {
+        let enemies: Vec<(Entity, Vec2)> = (0..n)
+            .map(|i| {
+                (
+                    Entity::from_raw_u32(i as u32 + 1).unwrap(),
+                    random_pos(&mut rng),
+                )
+            })
+            .collect();
+        let queries: Vec<Vec2> = (0..QUERIES).map(|_| random_pos(&mut rng)).collect();
+
+        let mut index = SpatialIndex::default();
+        let rebuild = time(|| {
+            index.clear();
+            for (ent, pos) in &enemies {
+                index.insert(*ent, *pos, ColliderTypes::Enemy);
+            }
+        });
+
+        let scan = time(|| {
+            for q in &queries {
+                black_box(enemies.iter().min_by(|a, b| {
+                    a.1.distance_squared(*q)
+                        .total_cmp(&b.1.distance_squared(*q))
+                }));
+            }
+        });
+        let nearest = time(|| {
+            for q in &queries {
+                black_box(index.nearest(*q, ColliderTypes::Enemy));
+            }
+        });
+        let radius = time(|| {
+            for q in &queries {
+                black_box(index.within_radius(*q, 200.0, ColliderTypes::Enemy).count());
+            }
+        });
+        println!(
+            "{:>8} {:>10.0}us {:>12.0}us {:>12.0}us {:>12.0}us",
+            n, rebuild, scan, nearest, radius
+        );
+    }
+}
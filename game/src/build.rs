@@ -1,18 +1,30 @@
-use std::time::Duration;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use bevy::{prelude::*, state::app::StatesPlugin};
+use bevy::{prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
 use clap::{Parser, Subcommand};
 use lightyear::prelude::{client::ClientPlugins, server::ServerPlugins};
 use serde::Deserialize;
 
 use crate::{
-    client::{ClientRenderPlugin, GameClientPlugin},
+    client::{
+        ClientRenderPlugin, GameClientPlugin,
+        demo_viewer::{DemoPlayback, DemoViewerPlugin},
+        replay::{ReplayFile, ReplayPlayback, ReplayPlaybackPlugin, ReplayRecorder},
+        simulate::{SimulationConfig, run_simulations},
+    },
     render::GameSharedRenderPlugin,
-    server::{DedicatedServerPlugin, DedicatedServerRendererPlugin, GameServerPlugin},
-    shared::GameSharedPlugin,
+    server::{
+        DedicatedServerPlugin, DedicatedServerRendererPlugin, GameServerPlugin, demo::DemoRecorder,
+    },
+    shared::{
+        GameSharedPlugin, game_rules::Difficulty, players::CharacterKind, weapons::WeaponKind,
+    },
 };
 
-const TICKRATE: f64 = 1.0 / 64.0;
+pub const TICKRATE: f64 = 1.0 / 64.0;
 /// Responsible for constructing the app when we launch the game via command line arguments
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -21,24 +33,96 @@ pub struct Cli {
     kind: Option<AppKind>,
 }
 
-#[derive(Subcommand, Debug, Clone, Copy)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum AppKind {
     Client {
         #[arg(short, long, default_value = None)]
         c_id: Option<u64>,
+        /// Records single player matches to this file, so that they can be replayed
+        #[arg(long)]
+        record: Option<PathBuf>,
     },
     Server {
         #[arg(short, long)]
         render: bool,
+        /// Records a demo of each match to this file, which can be watched with `demo`
+        #[arg(long)]
+        demo: Option<PathBuf>,
     },
+    /// Watches a demo that a server recorded
+    Demo { path: PathBuf },
+    /// Plays back a recorded single player match
+    Replay {
+        path: PathBuf,
+        #[arg(short, long)]
+        render: bool,
+    },
+    /// Plays single player matches headlessly with a simple AI, and prints a JSON summary of
+    /// each one. The kinds get written the same way as in RON, like `--weapon ThrowHands`
+    Simulate {
+        #[arg(long, default_value_t = 1)]
+        runs: u64,
+        #[arg(long, value_parser = parse_ron::<CharacterKind>, default_value = "Dewey")]
+        character: CharacterKind,
+        #[arg(long, value_parser = parse_ron::<WeaponKind>, default_value = "DiceGuard")]
+        weapon: WeaponKind,
+        #[arg(long, value_parser = parse_ron::<Difficulty>, default_value = "Easy")]
+        difficulty: Difficulty,
+        /// The first match uses this seed, and each one after it uses the next
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Shortens (or lengthens) every match, instead of the usual length
+        #[arg(long)]
+        minutes: Option<f32>,
+        /// Also writes every summary to this file, as one JSON array
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+fn parse_ron<T: serde::de::DeserializeOwned>(s: &str) -> Result<T, String> {
+    ron::from_str(s).map_err(|e| e.to_string())
 }
 
 impl Cli {
     pub fn build_game_app(&self, app: &mut App) {
-        let app_kind = self.kind.unwrap_or_else(|| panic!("App kind not found!"));
+        let app_kind = self
+            .kind
+            .clone()
+            .unwrap_or_else(|| panic!("App kind not found!"));
         match app_kind {
-            AppKind::Client { c_id } => build_game_client_app(app, c_id, true),
-            AppKind::Server { render } => build_game_server_app(app, render),
+            AppKind::Client { c_id, record } => {
+                build_game_client_app(app, c_id, true);
+                if let Some(path) = record {
+                    app.insert_resource(ReplayRecorder::new(path));
+                }
+            }
+            AppKind::Server { render, demo } => {
+                build_game_server_app(app, render);
+                if let Some(path) = demo {
+                    app.insert_resource(DemoRecorder::new(path));
+                }
+            }
+            AppKind::Demo { path } => build_demo_viewer_app(app, &path),
+            AppKind::Replay { path, render } => build_replay_app(app, &path, render),
+            AppKind::Simulate {
+                runs,
+                character,
+                weapon,
+                difficulty,
+                seed,
+                minutes,
+                out,
+            } => {
+                let config = SimulationConfig {
+                    character,
+                    weapon,
+                    difficulty,
+                    seed,
+                    match_length: minutes.map(|m| m * 60.0),
+                };
+                build_simulation_app(app, config, runs, out);
+            }
         }
     }
 }
@@ -78,6 +162,40 @@ pub fn build_game_server_app(app: &mut App, render: bool) {
     app.add_plugins(DedicatedServerPlugin);
 }
 
+pub fn build_replay_app(app: &mut App, path: &Path, render: bool) {
+    let replay = ReplayFile::load(path);
+    build_game_client_app(app, None, render);
+    if !render {
+        // Nobody's watching, so every update can be exactly one tick instead of waiting on the clock
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            TICKRATE,
+        )));
+    }
+    app.insert_resource(ReplayPlayback::new(replay, !render))
+        .add_plugins(ReplayPlaybackPlugin);
+}
+
+/// Each match gets an app of its own (see `simulate_match`), so this one only runs them all
+pub fn build_simulation_app(
+    app: &mut App,
+    config: SimulationConfig,
+    runs: u64,
+    out: Option<PathBuf>,
+) {
+    add_headless_app_plugins(app);
+    app.set_runner(move |_| {
+        run_simulations(config, runs, out.as_deref());
+        AppExit::Success
+    });
+}
+
+/// The demo viewer doesn't run any of the game, so it only needs bevy and the viewer itself
+pub fn build_demo_viewer_app(app: &mut App, path: &Path) {
+    add_bevy_default_app_plugins(app, "Demo Viewer".into());
+    app.insert_resource(DemoPlayback::load(path))
+        .add_plugins(DemoViewerPlugin);
+}
+
 pub fn add_lightyear_client_plugin(app: &mut App, tickrate: f64) {
     app.add_plugins(ClientPlugins {
         tick_duration: Duration::from_secs_f64(tickrate),
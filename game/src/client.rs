@@ -1,12 +1,18 @@
 use crate::{
     client::{
+        drops::ClientDropsRenderPlugin,
         enemies::ClientEnemyRenderPlugin,
         game_client::{GameClient, GameClientConfig},
+        hud::GameHudPlugin,
         load_game::ClientGameLoadingPlugin,
         lobby::ClientGameLobbyPlugin,
         main_menu::MainMenuPlugin,
+        match_clock::ClientMatchClockPlugin,
         mp_selection_menu::MPSelectionMenuPlugin,
+        pause_menu::PauseMenuPlugin,
         players::ClientPlayerRenderPlugin,
+        post_game::PostGameScreenPlugin,
+        props::ClientPropsRenderPlugin,
     },
     shared::{
         SEND_INTERVAL,
@@ -19,32 +25,51 @@ use lightyear::prelude::{Client, Predicted, ReplicationSender, Server, Timeline}
 
 pub mod camera;
 pub mod client_states;
+pub mod demo_viewer;
+pub mod drops;
 pub mod enemies;
 pub mod game_client;
+pub mod hud;
 pub mod load_game;
 pub mod lobby;
 pub mod main_menu;
+pub mod map;
+pub mod match_clock;
 pub mod mp_selection_menu;
+pub mod pause_menu;
 pub mod players;
+pub mod post_game;
 pub mod projectiles;
+pub mod props;
+pub mod replay;
+pub mod simulate;
 mod weapons;
 use camera::GameCameraClientPlugin;
 use client_states::ClientStatesPlugin;
+use drops::ClientDropsPlugin;
 use enemies::ClientEnemyPlugin;
+use map::ClientMapPlugin;
 use players::ClientPlayerPlugin;
 use projectiles::ClientProjectilePlugin;
+use props::ClientPropsPlugin;
+use replay::ClientReplayPlugin;
 use weapons::*;
 
 pub struct GameClientPlugin;
 impl Plugin for GameClientPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
+            ClientDropsPlugin,
             ClientEnemyPlugin,
             ClientStatesPlugin,
             ClientGameLobbyPlugin,
             ClientGameLoadingPlugin,
+            ClientMapPlugin,
+            ClientMatchClockPlugin,
             ClientPlayerPlugin,
             ClientProjectilePlugin,
+            ClientPropsPlugin,
+            ClientReplayPlugin,
             ClientWeaponsPlugin,
         ))
         .add_systems(Startup, move_to_first_app_state)
@@ -62,10 +87,15 @@ impl Plugin for ClientRenderPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             GameCameraClientPlugin,
+            GameHudPlugin,
+            ClientDropsRenderPlugin,
             ClientEnemyRenderPlugin,
             MainMenuPlugin,
             MPSelectionMenuPlugin,
+            PauseMenuPlugin,
+            PostGameScreenPlugin,
             ClientPlayerRenderPlugin,
+            ClientPropsRenderPlugin,
             ClientDiceGuardRenderPlugin,
         ));
     }
@@ -0,0 +1,251 @@
+//! Watches a demo that a dedicated server recorded.
+//!
+//! None of the game runs here. The viewer just draws whatever was in the snapshot at the
+//! current time (sliding things between snapshots so that it isn't choppy), so it doesn't need
+//! a server, the assets for everything, or a simulation that agrees with the one that recorded
+//! it.
+//!
+//! Space plays and pauses, the left and right arrows step through the snapshots (holding shift
+//! skips ten seconds at a time), up and down change the speed, and Home and End jump to the
+//! start and end. The camera is a free cam, so WASD moves it around and the scroll wheel zooms
+use std::path::Path;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    render::camera::{GameMainCamera, move_free_camera},
+    shared::{
+        demo::*,
+        map::{MapDefinition, ObstacleShape},
+    },
+};
+
+/// How far back events stay on screen, in seconds of match time
+const EVENT_WINDOW: f32 = 5.0;
+/// How far shift and an arrow key skips, in seconds
+const SKIP_SECONDS: f32 = 10.0;
+const MIN_SPEED: f32 = 0.125;
+const MAX_SPEED: f32 = 16.0;
+
+pub struct DemoViewerPlugin;
+
+impl Plugin for DemoViewerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_demo_viewer)
+            .add_systems(
+                Update,
+                (
+                    (demo_viewer_controls, advance_demo).chain(),
+                    (draw_demo_map, draw_demo_frame, update_demo_overlay),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, move_free_camera);
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct DemoPlayback {
+    pub demo: DemoFile,
+    map: MapDefinition,
+    /// Seconds into the match
+    pub time: f32,
+    pub playing: bool,
+    pub speed: f32,
+}
+
+impl DemoPlayback {
+    pub fn new(demo: DemoFile) -> Self {
+        Self {
+            map: MapDefinition::import(demo.rules.map_type),
+            demo,
+            time: 0.0,
+            playing: true,
+            speed: 1.0,
+        }
+    }
+
+    pub fn load(path: &Path) -> Self {
+        Self::new(DemoFile::load(path))
+    }
+
+    fn seek(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.demo.duration());
+    }
+
+    /// Moves `by` snapshots forwards or backwards from the one that's showing
+    fn step(&mut self, by: isize) {
+        let frame = self.demo.frame_at(self.time) as isize + by;
+        let last = self.demo.frames.len().saturating_sub(1) as isize;
+        if let Some(f) = self.demo.frames.get(frame.clamp(0, last) as usize) {
+            self.time = f.time;
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+struct DemoOverlay;
+
+fn spawn_demo_viewer(mut commands: Commands, playback: Res<DemoPlayback>) {
+    // Start out looking at wherever the first player was
+    let start = playback
+        .demo
+        .frames
+        .first()
+        .and_then(|f| f.entities.iter().find(|e| e.kind == DemoEntityKind::Player))
+        .map_or(Vec2::ZERO, |e| e.pos.as_vec2());
+    commands.spawn((
+        Camera2d,
+        GameMainCamera::default(),
+        Transform::from_translation(start.extend(0.0)),
+    ));
+    commands.spawn((
+        DemoOverlay,
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+    ));
+}
+
+fn demo_viewer_controls(keys: Res<ButtonInput<KeyCode>>, mut playback: ResMut<DemoPlayback>) {
+    let skip = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keys.just_pressed(KeyCode::Space) {
+        // Playing from the very end starts it over
+        if !playback.playing && playback.time >= playback.demo.duration() {
+            playback.time = 0.0;
+        }
+        playback.playing = !playback.playing;
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        if skip {
+            let time = playback.time + SKIP_SECONDS;
+            playback.seek(time);
+        } else {
+            playback.playing = false;
+            playback.step(1);
+        }
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        if skip {
+            let time = playback.time - SKIP_SECONDS;
+            playback.seek(time);
+        } else {
+            playback.playing = false;
+            playback.step(-1);
+        }
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        playback.speed = (playback.speed * 2.0).min(MAX_SPEED);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        playback.speed = (playback.speed / 2.0).max(MIN_SPEED);
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        playback.seek(0.0);
+    }
+    if keys.just_pressed(KeyCode::End) {
+        let end = playback.demo.duration();
+        playback.seek(end);
+    }
+}
+
+fn advance_demo(time: Res<Time>, mut playback: ResMut<DemoPlayback>) {
+    if !playback.playing {
+        return;
+    }
+    let next = playback.time + time.delta_secs() * playback.speed;
+    playback.seek(next);
+    if playback.time >= playback.demo.duration() {
+        playback.playing = false;
+    }
+}
+
+fn draw_demo_map(mut gizmos: Gizmos, playback: Res<DemoPlayback>) {
+    let map = &playback.map;
+    gizmos.rect_2d(map.bounds.center(), map.bounds.size(), Color::WHITE);
+    let color = Color::srgb(0.5, 0.5, 0.5);
+    for obstacle in &map.obstacles {
+        match obstacle.shape {
+            ObstacleShape::Rectangle { width, height } => {
+                gizmos.rect_2d(obstacle.center, Vec2::new(width, height), color)
+            }
+            ObstacleShape::Circle { radius } => gizmos.circle_2d(obstacle.center, radius, color),
+        };
+    }
+}
+
+fn kind_style(kind: DemoEntityKind) -> (f32, Color) {
+    match kind {
+        DemoEntityKind::Player => (16.0, Color::srgb(0.2, 0.6, 1.0)),
+        DemoEntityKind::Enemy(_) => (12.0, Color::srgb(0.9, 0.2, 0.2)),
+        DemoEntityKind::Elite(_) => (16.0, Color::srgb(1.0, 0.5, 0.0)),
+        DemoEntityKind::Boss(_) => (32.0, Color::srgb(0.7, 0.0, 0.7)),
+        DemoEntityKind::Projectile(_) => (3.0, Color::srgb(1.0, 1.0, 0.6)),
+        DemoEntityKind::XPGem => (4.0, Color::srgb(0.2, 0.9, 0.4)),
+        DemoEntityKind::Pickup(_) => (8.0, Color::srgb(1.0, 0.85, 0.0)),
+        DemoEntityKind::Prop(_) => (20.0, Color::srgb(0.6, 0.4, 0.2)),
+    }
+}
+
+fn draw_demo_frame(mut gizmos: Gizmos, playback: Res<DemoPlayback>) {
+    let frames = &playback.demo.frames;
+    let i = playback.demo.frame_at(playback.time);
+    let Some(frame) = frames.get(i) else {
+        return;
+    };
+    // Slides everything towards where it is in the next snapshot, if it's still around then
+    let (next, t) = match frames.get(i + 1) {
+        Some(next) if next.time > frame.time => {
+            let t = ((playback.time - frame.time) / (next.time - frame.time)).clamp(0.0, 1.0);
+            let positions: HashMap<u64, Vec2> = next
+                .entities
+                .iter()
+                .map(|e| (e.id, e.pos.as_vec2()))
+                .collect();
+            (positions, t)
+        }
+        _ => (HashMap::default(), 0.0),
+    };
+
+    for entity in &frame.entities {
+        let from = entity.pos.as_vec2();
+        let pos = next.get(&entity.id).map_or(from, |to| from.lerp(*to, t));
+        let (radius, color) = kind_style(entity.kind);
+        gizmos.circle_2d(pos, radius, color);
+        if let Some(health) = entity.health {
+            let width = radius * 2.0;
+            let start = pos + Vec2::new(-radius, radius + 4.0);
+            gizmos.line_2d(
+                start,
+                start + Vec2::X * width * health as f32 / 100.0,
+                Color::srgb(0.2, 1.0, 0.2),
+            );
+        }
+    }
+}
+
+fn format_time(secs: f32) -> String {
+    let secs = secs.max(0.0) as u32;
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+fn update_demo_overlay(
+    playback: Res<DemoPlayback>,
+    mut q_overlay: Single<&mut Text, With<DemoOverlay>>,
+) {
+    let mut text = format!(
+        "{} / {}  x{}{}",
+        format_time(playback.time),
+        format_time(playback.demo.duration()),
+        playback.speed,
+        if playback.playing { "" } else { "  (paused)" },
+    );
+    for event in playback.demo.events_before(playback.time, EVENT_WINDOW) {
+        text.push_str(&format!("\n{}  {:?}", format_time(event.time), event.kind));
+    }
+    q_overlay.0 = text;
+}
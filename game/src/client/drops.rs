@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use crate::{
+    render::drops::rendering_on_xp_gem_add,
+    shared::{
+        combat::CombatSystemSet,
+        drops::add_non_replicated_gem_components,
+        game_kinds::{DefaultClientFilter, SinglePlayer},
+        stats::xp::add_xp,
+    },
+};
+
+/// XP only gets handed out where the game is simulated, so in multiplayer the client just
+/// waits for the server's level managers to be replicated
+pub struct ClientDropsPlugin;
+
+impl Plugin for ClientDropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            add_xp::<With<SinglePlayer>>.in_set(CombatSystemSet::Combat),
+        )
+        .add_observer(add_non_replicated_gem_components::<DefaultClientFilter>);
+    }
+}
+
+pub struct ClientDropsRenderPlugin;
+impl Plugin for ClientDropsRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            rendering_on_xp_gem_add::<Or<(With<SinglePlayer>, With<Predicted>)>>,
+        );
+    }
+}
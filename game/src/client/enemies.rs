@@ -1,11 +1,17 @@
 use crate::{
-    render::enemies::rendering_on_enemy_add,
+    render::enemies::{rendering_on_enemy_add, rendering_on_enemy_projectile_add},
     shared::{
         colliders::CommonColliderBundle,
         combat::CombatSystemSet,
-        enemies::{spawner::*, *},
+        enemies::{
+            behaviours::enemy_behaviours, bosses::*, elites::*, lod::*,
+            navigation::update_flow_fields, ranged::enemy_ranged_attack, spawner::*, targeting::*,
+            *,
+        },
         game_kinds::{DefaultClientFilter, SinglePlayer, is_single_player},
-        states::InGameState,
+        pickups::hold_frozen_enemies,
+        spatial_index::rebuild_spatial_index,
+        states::AppState,
     },
 };
 use bevy::prelude::*;
@@ -16,21 +22,49 @@ pub struct ClientEnemyPlugin;
 impl Plugin for ClientEnemyPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            OnEnter(InGameState::InGame),
-            spawn_enemy_spawn_manager.run_if(is_single_player),
+            OnEnter(AppState::InGame),
+            (spawn_enemy_spawn_manager, spawn_boss_director).run_if(is_single_player),
         )
         .add_systems(
             FixedUpdate,
             (
-                update_enemy_spawn_manager.run_if(resource_exists::<EnemySpawnManager>),
-                enemy_state_machine::<
-                    Or<(With<Predicted>, With<SinglePlayer>)>,
-                    Or<(With<Predicted>, With<SinglePlayer>)>,
-                >,
+                rebuild_spatial_index::<DefaultClientFilter>,
+                update_flow_fields::<DefaultClientFilter>,
+            )
+                .in_set(CombatSystemSet::PreCombat),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                update_enemy_spawn_manager::<With<SinglePlayer>>
+                    .run_if(resource_exists::<EnemySpawnManager>),
+                update_boss_director::<With<SinglePlayer>>.run_if(resource_exists::<BossDirector>),
+                (
+                    update_boss_phases::<DefaultClientFilter>,
+                    apply_boss_phase::<DefaultClientFilter>,
+                    enemy_state_machine::<
+                        Or<(With<Predicted>, With<SinglePlayer>)>,
+                        Or<(With<Predicted>, With<SinglePlayer>)>,
+                    >,
+                    enemy_behaviours::<
+                        Or<(With<Predicted>, With<SinglePlayer>)>,
+                        Or<(With<Predicted>, With<SinglePlayer>)>,
+                    >,
+                    hold_frozen_enemies::<DefaultClientFilter>,
+                    update_enemy_lod::<DefaultClientFilter, DefaultClientFilter>,
+                    integrate_simplified_enemies::<DefaultClientFilter>,
+                )
+                    .chain(),
+                // Only the server gets to fire in multiplayer
+                enemy_ranged_attack::<With<SinglePlayer>, With<SinglePlayer>>,
+                (threat_from_damage, apply_threat::<With<SinglePlayer>>).chain(),
+                elite_lifesteal::<With<SinglePlayer>>,
             )
                 .in_set(CombatSystemSet::Combat),
         )
-        .add_observer(add_non_replicated_enemy_components::<DefaultClientFilter>);
+        .add_observer(add_non_replicated_enemy_components::<DefaultClientFilter>)
+        .add_observer(on_enemy_death::<With<SinglePlayer>>)
+        .add_observer(on_elite_death::<With<SinglePlayer>>);
     }
 }
 
@@ -39,7 +73,10 @@ impl Plugin for ClientEnemyRenderPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            rendering_on_enemy_add::<Or<(With<SinglePlayer>, With<Predicted>)>>,
+            (
+                rendering_on_enemy_add::<Or<(With<SinglePlayer>, With<Predicted>)>>,
+                rendering_on_enemy_projectile_add::<Or<(With<SinglePlayer>, With<Predicted>)>>,
+            ),
         );
     }
 }
@@ -0,0 +1,550 @@
+//! The heads up display that the local player sees during a game.
+//!
+//! Everything in here reads off of the entities that the client is simulating (predicted
+//! in multiplayer, and the `SinglePlayer` ones otherwise), so the HUD reacts on the same frame
+//! as the thing that the player sees moving around on screen
+use bevy::{asset::LoadState, prelude::*};
+use lightyear::prelude::{Controlled, Predicted};
+
+use crate::{
+    shared::{
+        combat::Cooldown,
+        enemies::{Enemy, bosses::Boss, definitions::EnemyRegistry},
+        game_kinds::{DefaultClientFilter, SinglePlayer},
+        match_clock::MatchClock,
+        players::Player,
+        states::AppState,
+        stats::{components::Health, xp::LevelManager},
+        weapons::{Weapon, WeaponActiveTimer, WeaponKind},
+    },
+    utils::AssetFolder,
+};
+
+pub struct GameHudPlugin;
+
+impl Plugin for GameHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_health_display,
+                    update_xp_display,
+                    update_clock_display,
+                    (
+                        sync_weapon_slots,
+                        replace_missing_weapon_icons,
+                        update_weapon_slots,
+                    )
+                        .chain(),
+                    (sync_teammate_bars, update_teammate_bars).chain(),
+                    update_boss_bar,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// The player that the HUD is about. This is the one the client controls, either through
+/// lightyear in multiplayer, or by virtue of being the only one in single player
+type LocalPlayerFilter = (
+    With<Player>,
+    Or<(With<SinglePlayer>, (With<Predicted>, With<Controlled>))>,
+);
+
+/// Everybody else on the team. These only ever exist in multiplayer
+type TeammateFilter = (With<Player>, With<Predicted>, Without<Controlled>);
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_hud_root())]
+pub struct HudRoot;
+
+fn node_hud_root() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        height: Val::Percent(100.0),
+        width: Val::Percent(100.0),
+        flex_direction: FlexDirection::Column,
+        justify_content: JustifyContent::SpaceBetween,
+        padding: UiRect::all(Val::Px(8.0)),
+        ..default()
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_hud_row())]
+struct HudTopRow;
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_hud_row())]
+struct HudBottomRow;
+
+fn node_hud_row() -> Node {
+    Node {
+        width: Val::Percent(100.0),
+        flex_direction: FlexDirection::Row,
+        justify_content: JustifyContent::SpaceBetween,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(8.0),
+        ..default()
+    }
+}
+
+/// The empty part of any bar in the HUD. The fill is a child that gets its width changed
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_bar_background(), BackgroundColor = BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.8)))]
+struct HudBar;
+
+fn node_bar_background() -> Node {
+    Node {
+        height: Val::Px(16.0),
+        flex_grow: 1.0,
+        ..default()
+    }
+}
+
+fn node_bar_fill() -> Node {
+    Node {
+        height: Val::Percent(100.0),
+        width: Val::Percent(100.0),
+        ..default()
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudXPFill;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudLevelText;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudClockText;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudHealthFill;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudHealthText;
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_weapon_row())]
+pub struct HudWeaponRow;
+
+fn node_weapon_row() -> Node {
+    Node {
+        flex_direction: FlexDirection::Row,
+        column_gap: Val::Px(4.0),
+        ..default()
+    }
+}
+
+/// One per weapon that the local player has equipped
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_weapon_slot())]
+pub struct HudWeaponSlot {
+    pub weapon: Entity,
+}
+
+fn node_weapon_slot() -> Node {
+    Node {
+        height: Val::Px(48.0),
+        width: Val::Px(48.0),
+        flex_direction: FlexDirection::Column,
+        justify_content: JustifyContent::FlexEnd,
+        ..default()
+    }
+}
+
+/// Darkens the weapon icon from the top down while the weapon is on cooldown
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudWeaponCooldown;
+
+/// Shrinks along the bottom of the icon while the weapon is active
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudWeaponActive;
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_teammate_column())]
+pub struct HudTeammateColumn;
+
+fn node_teammate_column() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        left: Val::Px(8.0),
+        top: Val::Percent(30.0),
+        width: Val::Px(120.0),
+        flex_direction: FlexDirection::Column,
+        row_gap: Val::Px(4.0),
+        ..default()
+    }
+}
+
+/// Sits across the top of the screen while a boss is around, and is hidden otherwise
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_boss_bar())]
+pub struct HudBossBar;
+
+fn node_boss_bar() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        top: Val::Px(40.0),
+        left: Val::Percent(25.0),
+        width: Val::Percent(50.0),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(2.0),
+        display: Display::None,
+        ..default()
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudBossName;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudBossFill;
+
+/// A compact health bar for somebody else on the team
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudTeammateBar {
+    pub player: Entity,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudTeammateFill;
+
+fn spawn_hud(mut commands: Commands) {
+    let root = commands
+        .spawn((HudRoot, DespawnOnExit(AppState::InGame)))
+        .id();
+
+    // XP bar, level and clock along the top
+    let top_row = commands.spawn((HudTopRow, ChildOf(root))).id();
+    commands.spawn((HudLevelText, Text::new("Lv 1"), ChildOf(top_row)));
+    let xp_bar = commands.spawn((HudBar, ChildOf(top_row))).id();
+    commands.spawn((
+        HudXPFill,
+        node_bar_fill(),
+        BackgroundColor(Color::srgb(0.2, 0.6, 1.0)),
+        ChildOf(xp_bar),
+    ));
+    commands.spawn((HudClockText, Text::new("00:00"), ChildOf(top_row)));
+
+    // Health and weapons along the bottom
+    let bottom_row = commands.spawn((HudBottomRow, ChildOf(root))).id();
+    let health_bar = commands.spawn((HudBar, ChildOf(bottom_row))).id();
+    commands.spawn((
+        HudHealthFill,
+        node_bar_fill(),
+        BackgroundColor(Color::srgb(0.8, 0.1, 0.1)),
+        ChildOf(health_bar),
+    ));
+    commands.spawn((HudHealthText, Text::new(""), ChildOf(bottom_row)));
+    commands.spawn((HudWeaponRow, ChildOf(bottom_row)));
+
+    commands.spawn((HudTeammateColumn, ChildOf(root)));
+
+    // The boss bar, which stays hidden until there's a boss
+    let boss_bar = commands.spawn((HudBossBar, ChildOf(root))).id();
+    commands.spawn((HudBossName, Text::new(""), ChildOf(boss_bar)));
+    let boss_health = commands
+        .spawn((
+            HudBar,
+            Node {
+                width: Val::Percent(100.0),
+                ..node_bar_background()
+            },
+            ChildOf(boss_bar),
+        ))
+        .id();
+    commands.spawn((
+        HudBossFill,
+        node_bar_fill(),
+        BackgroundColor(Color::srgb(0.6, 0.1, 0.6)),
+        ChildOf(boss_health),
+    ));
+}
+
+fn update_health_display(
+    q_player: Query<&Health, LocalPlayerFilter>,
+    mut q_fill: Query<&mut Node, With<HudHealthFill>>,
+    mut q_text: Query<&mut Text, With<HudHealthText>>,
+) {
+    let Some(health) = q_player.iter().next() else {
+        return;
+    };
+    for mut node in &mut q_fill {
+        node.width = Val::Percent(fill_percent(health.current, health.max));
+    }
+    for mut text in &mut q_text {
+        text.0 = format!("{:.0} / {:.0}", health.current, health.max);
+    }
+}
+
+/// Shows our own level if we're leveling per player, and the party's level otherwise
+fn update_xp_display(
+    q_own_level: Query<&LevelManager, LocalPlayerFilter>,
+    q_party_level: Query<&LevelManager, (Without<Player>, DefaultClientFilter)>,
+    mut q_fill: Query<&mut Node, With<HudXPFill>>,
+    mut q_text: Query<&mut Text, With<HudLevelText>>,
+) {
+    let Some(level) = q_own_level.iter().next().or(q_party_level.iter().next()) else {
+        return;
+    };
+    // The bar runs from the last level threshold to the next one, rather than from zero
+    let progress = level.c_xp - level.prev_max;
+    let needed = level.next_max - level.prev_max;
+    for mut node in &mut q_fill {
+        node.width = Val::Percent(fill_percent(progress, needed));
+    }
+    for mut text in &mut q_text {
+        text.0 = format!("Lv {}", level.c_level);
+    }
+}
+
+/// The clock is owned by the server, so in multiplayer this is reading the replicated copy
+fn update_clock_display(
+    q_clock: Query<&MatchClock>,
+    mut q_text: Query<&mut Text, With<HudClockText>>,
+) {
+    let Some(clock) = q_clock.iter().next() else {
+        return;
+    };
+    let secs = clock.elapsed as u32;
+    for mut text in &mut q_text {
+        text.0 = format!("{:02}:{:02}", secs / 60, secs % 60);
+    }
+}
+
+/// Makes sure that there's a slot for every weapon on the local player, and gets rid of
+/// slots for weapons that have gone away
+fn sync_weapon_slots(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    q_player: Query<Entity, LocalPlayerFilter>,
+    q_weapons: Query<(Entity, &Weapon, &ChildOf), DefaultClientFilter>,
+    q_row: Query<Entity, With<HudWeaponRow>>,
+    q_slots: Query<(Entity, &HudWeaponSlot)>,
+) {
+    let Some(row) = q_row.iter().next() else {
+        return;
+    };
+    let Some(player) = q_player.iter().next() else {
+        return;
+    };
+    for (slot_ent, slot) in &q_slots {
+        if q_weapons.get(slot.weapon).is_err() {
+            commands.entity(slot_ent).despawn();
+        }
+    }
+    for (w_ent, weapon, parent) in &q_weapons {
+        if parent.parent() != player || q_slots.iter().any(|(_, s)| s.weapon == w_ent) {
+            continue;
+        }
+        let icon = AssetFolder::from(weapon.kind()).to_path(WEAPON_ICON_FILE.into());
+        let slot = commands
+            .spawn((
+                HudWeaponSlot { weapon: w_ent },
+                ImageNode::new(assets.load(icon)),
+                ChildOf(row),
+            ))
+            .id();
+        commands.spawn((
+            HudWeaponCooldown,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(0.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ChildOf(slot),
+        ));
+        commands.spawn((
+            HudWeaponActive,
+            Node {
+                height: Val::Px(4.0),
+                width: Val::Percent(0.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(1.0, 0.85, 0.2)),
+            ChildOf(slot),
+        ));
+    }
+}
+
+/// What a weapon's icon is called inside of its asset folder
+const WEAPON_ICON_FILE: &str = "icon.png";
+
+/// Stands in for the icon of a weapon that doesn't have one, so the slots can still be told apart
+fn placeholder_icon_color(kind: WeaponKind) -> Color {
+    match kind {
+        WeaponKind::DiceGuard => Color::srgb(0.85, 0.85, 0.9),
+        WeaponKind::ThrowHands => Color::srgb(0.8, 0.35, 0.3),
+        WeaponKind::PaddleBack => Color::srgb(0.3, 0.5, 0.8),
+        WeaponKind::FlurryOfBlows => Color::srgb(0.4, 0.7, 0.35),
+    }
+}
+
+/// The capitals in the weapon's name, like "DG" for the dice guard
+fn weapon_initials(kind: WeaponKind) -> String {
+    kind.display_name()
+        .chars()
+        .filter(|c| c.is_ascii_uppercase())
+        .collect()
+}
+
+/// Not every weapon has its art yet, so any icon that fails to load gets swapped out for a
+/// placeholder, rather than leaving the slot empty
+fn replace_missing_weapon_icons(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    q_slots: Query<(Entity, &HudWeaponSlot, &ImageNode)>,
+    q_weapons: Query<&Weapon>,
+) {
+    for (slot_ent, slot, image) in &q_slots {
+        if !matches!(assets.load_state(image.image.id()), LoadState::Failed(_)) {
+            continue;
+        }
+        let Ok(weapon) = q_weapons.get(slot.weapon) else {
+            continue;
+        };
+        commands
+            .entity(slot_ent)
+            .remove::<ImageNode>()
+            .insert(BackgroundColor(placeholder_icon_color(weapon.kind())));
+        commands.spawn((
+            Text::new(weapon_initials(weapon.kind())),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(4.0),
+                left: Val::Px(4.0),
+                ..default()
+            },
+            ChildOf(slot_ent),
+        ));
+    }
+}
+
+fn update_weapon_slots(
+    q_slots: Query<(&HudWeaponSlot, &Children)>,
+    q_weapons: Query<(Option<&Cooldown>, Option<&WeaponActiveTimer>), With<Weapon>>,
+    mut q_cooldown: Query<&mut Node, (With<HudWeaponCooldown>, Without<HudWeaponActive>)>,
+    mut q_active: Query<&mut Node, (With<HudWeaponActive>, Without<HudWeaponCooldown>)>,
+) {
+    for (slot, children) in &q_slots {
+        let Ok((m_cd, m_active)) = q_weapons.get(slot.weapon) else {
+            continue;
+        };
+        let cd_remaining = m_cd.map(|cd| cd.fraction_remaining()).unwrap_or(0.0);
+        let active_remaining = m_active.map(|a| a.fraction_remaining()).unwrap_or(0.0);
+        for child in children.iter() {
+            if let Ok(mut node) = q_cooldown.get_mut(child) {
+                node.height = Val::Percent(cd_remaining * 100.0);
+            }
+            if let Ok(mut node) = q_active.get_mut(child) {
+                node.width = Val::Percent(active_remaining * 100.0);
+            }
+        }
+    }
+}
+
+fn sync_teammate_bars(
+    mut commands: Commands,
+    q_teammates: Query<Entity, TeammateFilter>,
+    q_column: Query<Entity, With<HudTeammateColumn>>,
+    q_bars: Query<(Entity, &HudTeammateBar)>,
+) {
+    let Some(column) = q_column.iter().next() else {
+        return;
+    };
+    for (bar_ent, bar) in &q_bars {
+        if q_teammates.get(bar.player).is_err() {
+            commands.entity(bar_ent).despawn();
+        }
+    }
+    for player in &q_teammates {
+        if q_bars.iter().any(|(_, b)| b.player == player) {
+            continue;
+        }
+        let bar = commands
+            .spawn((
+                HudTeammateBar { player },
+                HudBar,
+                Node {
+                    height: Val::Px(8.0),
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                ChildOf(column),
+            ))
+            .id();
+        commands.spawn((
+            HudTeammateFill,
+            node_bar_fill(),
+            BackgroundColor(Color::srgb(0.2, 0.8, 0.2)),
+            ChildOf(bar),
+        ));
+    }
+}
+
+fn update_teammate_bars(
+    q_bars: Query<(&HudTeammateBar, &Children)>,
+    q_health: Query<&Health, TeammateFilter>,
+    mut q_fill: Query<&mut Node, With<HudTeammateFill>>,
+) {
+    for (bar, children) in &q_bars {
+        let Ok(health) = q_health.get(bar.player) else {
+            continue;
+        };
+        for child in children.iter() {
+            if let Ok(mut node) = q_fill.get_mut(child) {
+                node.width = Val::Percent(fill_percent(health.current, health.max));
+            }
+        }
+    }
+}
+
+/// Health is predicted along with the rest of the boss, so this is the same on every client.
+/// If there's ever more than one boss out at once, the bar follows whichever is closest to death
+fn update_boss_bar(
+    registry: Res<EnemyRegistry>,
+    q_boss: Query<(&Enemy, &Health), (With<Boss>, DefaultClientFilter)>,
+    mut q_bar: Query<&mut Node, (With<HudBossBar>, Without<HudBossFill>)>,
+    mut q_fill: Query<&mut Node, (With<HudBossFill>, Without<HudBossBar>)>,
+    mut q_name: Query<&mut Text, With<HudBossName>>,
+) {
+    let boss = q_boss.iter().min_by(|a, b| {
+        fill_percent(a.1.current, a.1.max).total_cmp(&fill_percent(b.1.current, b.1.max))
+    });
+    for mut node in &mut q_bar {
+        node.display = if boss.is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    let Some((enemy, health)) = boss else {
+        return;
+    };
+    for mut node in &mut q_fill {
+        node.width = Val::Percent(fill_percent(health.current, health.max));
+    }
+    for mut text in &mut q_name {
+        text.0.clone_from(&registry.get(enemy.kind).name);
+    }
+}
+
+/// Turns a current/max pair into a percentage for a `Val`, guarding against empty bars
+fn fill_percent(current: f32, max: f32) -> f32 {
+    if max <= 0.0 {
+        0.0
+    } else {
+        (current / max).clamp(0.0, 1.0) * 100.0
+    }
+}
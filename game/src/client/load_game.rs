@@ -9,20 +9,29 @@ use crate::shared::{
     game_object_spawning::{self, spawn_game_object},
     game_rules::GameRules,
     lobby::{ClientStartGameMessage, ServerStartLoadingGameMessage},
+    map::{CurrentMap, load_map},
     players::*,
+    rng::{MatchRng, RngStream, RollbackRng, seed_match_rng},
     states::{AppState, InGameState},
     stats::{RawStatsList, xp::add_level_manager},
-    weapons::{WeaponKind, add_weapon_to_player},
+    weapons::add_weapon_to_player,
 };
 
 pub struct ClientGameLoadingPlugin;
 
 impl Plugin for ClientGameLoadingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<PlayerLoadout>().add_systems(
             OnEnter(AppState::LoadingLevel),
             (
-                (spawn_player_character, add_level_manager).run_if(is_single_player),
+                (
+                    spawn_player_character,
+                    add_level_manager::<With<SinglePlayer>>,
+                )
+                    .chain()
+                    .after(load_map)
+                    .after(seed_match_rng)
+                    .run_if(is_single_player),
                 tmp_move_to_game,
             ),
         );
@@ -47,10 +56,18 @@ fn tmp_move_to_game(
     game_state.set(InGameState::InGame);
 }
 
-/// Very tmp while I don't have a query anywhwere for user's character selection
-fn spawn_player_character(mut commands: Commands, game_kinds: Res<CurrentGameKind>) {
-    let mut rng = rand::rng();
-    let pos = (rng.random_range(-50.0..50.0), rng.random_range(-50.0..50.0));
+/// Very tmp while I don't have a query anywhwere for user's character selection.
+/// Until then, the loadout is always the default one, unless something (like a simulation) asks
+/// for another
+fn spawn_player_character(
+    mut commands: Commands,
+    game_kinds: Res<CurrentGameKind>,
+    loadout: Res<PlayerLoadout>,
+    map: Res<CurrentMap>,
+    mut match_rng: ResMut<MatchRng>,
+) {
+    let rng = match_rng.stream(RngStream::Players);
+    let pos = map.player_start.random_point(rng);
     let player = Player {
         client: PeerId::Local(0),
     };
@@ -59,16 +76,11 @@ fn spawn_player_character(mut commands: Commands, game_kinds: Res<CurrentGameKin
         &mut commands,
         game_kinds.0.unwrap(),
         MultiPlayerComponentOptions::from(player),
-        (player, Position(Vec2::new(pos.0, pos.1))),
+        (player, Position(pos), RollbackRng::new(rng.random())),
     );
 
-    let stats = RawStatsList::import_stats(CharacterKind::Dewey);
+    let stats = RawStatsList::import_stats(loadout.character);
     stats.apply_to_character(p_ent, &mut commands);
 
-    add_weapon_to_player(
-        p_ent,
-        WeaponKind::DiceGuard,
-        &mut commands,
-        game_kinds.0.unwrap(),
-    );
+    add_weapon_to_player(p_ent, loadout.weapon, &mut commands, game_kinds.0.unwrap());
 }
@@ -0,0 +1,24 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::shared::{
+    combat::CombatSystemSet,
+    game_kinds::DefaultClientFilter,
+    map::{CurrentMap, clamp_to_map_bounds},
+};
+
+/// The bounds are part of the simulation, so predicted players and enemies get kept inside of
+/// them too, rather than waiting on the server to pull them back
+pub struct ClientMapPlugin;
+
+impl Plugin for ClientMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedPostUpdate,
+            clamp_to_map_bounds::<DefaultClientFilter>
+                .after(PhysicsSystems::Last)
+                .in_set(CombatSystemSet::PostPhysicsSet)
+                .run_if(resource_exists::<CurrentMap>),
+        );
+    }
+}
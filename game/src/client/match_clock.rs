@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use crate::shared::{
+    combat::CombatSystemSet,
+    game_kinds::{SinglePlayer, is_single_player},
+    match_clock::*,
+    states::{AppState, InGameState},
+};
+
+pub struct ClientMatchClockPlugin;
+
+impl Plugin for ClientMatchClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            add_match_clock.run_if(is_single_player),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                tick_match_clock::<With<SinglePlayer>>,
+                check_match_end::<With<SinglePlayer>, With<SinglePlayer>>.pipe(write_match_ended),
+            )
+                .chain()
+                .in_set(CombatSystemSet::PostCombatUpdate)
+                .run_if(in_state(InGameState::InGame).and(is_single_player)),
+        )
+        .add_systems(
+            Update,
+            (
+                client_receive_match_ended.run_if(not(is_single_player)),
+                move_to_post_game,
+            )
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+/// In multiplayer, the server is the one that decides when the match is over
+fn client_receive_match_ended(
+    mut messages: MessageWriter<MatchEndedMessage>,
+    mut q_receiver: Query<&mut MessageReceiver<MatchEndedMessage>>,
+) {
+    for mut rec in &mut q_receiver {
+        for m in rec.receive() {
+            messages.write(m);
+        }
+    }
+}
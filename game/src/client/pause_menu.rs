@@ -0,0 +1,265 @@
+//! The menu that comes up when the player hits escape during a game.
+//!
+//! What "pausing" means depends on the kind of game being played. In single player, we move
+//! `InGameState` to `Paused`, which actually stops the simulation. In multiplayer, the server
+//! keeps running no matter what, so we only ever touch `PauseState`, which is just the overlay
+use bevy::{
+    prelude::*,
+    window::{MonitorSelection, PrimaryWindow, WindowMode},
+};
+use lightyear::prelude::Client;
+
+use crate::{
+    render::ui::button::*,
+    shared::{
+        game_kinds::{CurrentGameKind, is_single_player},
+        states::{AppState, InGameState, PauseState},
+    },
+};
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, register_buttons)
+            .add_systems(
+                Update,
+                toggle_pause
+                    .run_if(in_state(AppState::InGame).and(escape_pressed))
+                    .before(close_pause_settings),
+            )
+            .add_systems(
+                Update,
+                close_pause_settings.run_if(in_state(PauseState::Paused).and(escape_pressed)),
+            )
+            .add_systems(OnEnter(PauseState::Paused), spawn_pause_menu);
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_pause_menu_screen())]
+pub struct PauseMenuScreen;
+
+fn node_pause_menu_screen() -> Node {
+    Node {
+        height: Val::Percent(100.0),
+        width: Val::Percent(100.0),
+        display: Display::Flex,
+        flex_direction: FlexDirection::Column,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_pause_button_well())]
+struct PauseButtonWell;
+
+fn node_pause_button_well() -> Node {
+    Node {
+        width: Val::Percent(40.0),
+        height: Val::Percent(50.0),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::SpaceBetween,
+        ..default()
+    }
+}
+
+/// Sits on top of the button well while it's open, so that we can get rid of it to go back
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_pause_settings_panel())]
+pub struct PauseSettingsPanel;
+
+fn node_pause_settings_panel() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        ..node_pause_button_well()
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ButtonResumeGame;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ButtonPauseSettings;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ButtonLeaveGame;
+
+fn register_buttons(world: &mut World) {
+    let resume_sys = world.register_system(resume_game);
+    let settings_sys = world.register_system(open_pause_settings);
+    let close_settings_sys = world.register_system(close_pause_settings);
+    let fullscreen_sys = world.register_system(toggle_fullscreen);
+    let leave_sys = world.register_system(leave_game);
+    let mut button_systems = world.resource_mut::<ButtonSystems>();
+
+    (*button_systems).insert("resume_game".into(), resume_sys);
+    (*button_systems).insert("open_pause_settings".into(), settings_sys);
+    (*button_systems).insert("close_pause_settings".into(), close_settings_sys);
+    (*button_systems).insert("toggle_fullscreen".into(), fullscreen_sys);
+    (*button_systems).insert("leave_game".into(), leave_sys);
+}
+
+fn escape_pressed(button: Res<ButtonInput<KeyCode>>) -> bool {
+    button.just_pressed(KeyCode::Escape)
+}
+
+/// Escape opens the menu, and pressing it again (with the settings closed) takes you back to the game
+fn toggle_pause(
+    pause_state: Res<State<PauseState>>,
+    game_state: Res<State<InGameState>>,
+    game_kind: Res<CurrentGameKind>,
+    q_settings: Query<(), With<PauseSettingsPanel>>,
+    mut next_pause: ResMut<NextState<PauseState>>,
+    mut next_game: ResMut<NextState<InGameState>>,
+) {
+    match pause_state.get() {
+        PauseState::Unpaused => {
+            next_pause.set(PauseState::Paused);
+            if is_single_player(game_kind) && *game_state.get() == InGameState::InGame {
+                next_game.set(InGameState::Paused);
+            }
+        }
+        PauseState::Paused => {
+            // The settings panel gets the first crack at escape
+            if q_settings.is_empty() {
+                next_pause.set(PauseState::Unpaused);
+                if *game_state.get() == InGameState::Paused {
+                    next_game.set(InGameState::InGame);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands, assets: Res<AssetServer>, systems: Res<ButtonSystems>) {
+    let pause_screen = commands
+        .spawn((
+            PauseMenuScreen,
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ZIndex(4),
+            DespawnOnExit(PauseState::Paused),
+        ))
+        .with_child(Text::new("Paused"))
+        .id();
+
+    let button_well = commands
+        .spawn((PauseButtonWell, ChildOf(pause_screen)))
+        .id();
+
+    let resume = spawn_pause_button(&mut commands, &assets, &systems, "resume_game", "Resume");
+    let settings = spawn_pause_button(
+        &mut commands,
+        &assets,
+        &systems,
+        "open_pause_settings",
+        "Settings",
+    );
+    let leave = spawn_pause_button(&mut commands, &assets, &systems, "leave_game", "Leave Game");
+
+    commands
+        .entity(resume)
+        .insert((ButtonResumeGame, ChildOf(button_well)));
+    commands
+        .entity(settings)
+        .insert((ButtonPauseSettings, ChildOf(button_well)));
+    commands
+        .entity(leave)
+        .insert((ButtonLeaveGame, ChildOf(button_well)));
+}
+
+/// All of the buttons in the pause menu look the same, and just differ by what they trigger
+fn spawn_pause_button(
+    commands: &mut Commands,
+    assets: &Res<AssetServer>,
+    systems: &ButtonSystems,
+    sys_name: &str,
+    text: &str,
+) -> Entity {
+    let sys = systems.get(sys_name).unwrap();
+    let button = GameButton::new(GameButtonOnRelease::TriggerSystem(*sys));
+    let style = GameButtonStyle::new(GameButtonImage::default())
+        .with_color(Color::srgb(1.0, 0.0, 0.0))
+        .with_size(Val::Percent(30.0), Val::Percent(100.0))
+        .with_text(text.into());
+    button.spawn(commands, assets, style)
+}
+
+fn resume_game(
+    game_state: Res<State<InGameState>>,
+    mut next_pause: ResMut<NextState<PauseState>>,
+    mut next_game: ResMut<NextState<InGameState>>,
+) {
+    next_pause.set(PauseState::Unpaused);
+    if *game_state.get() == InGameState::Paused {
+        next_game.set(InGameState::InGame);
+    }
+}
+
+fn open_pause_settings(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    systems: Res<ButtonSystems>,
+    q_screen: Single<Entity, With<PauseMenuScreen>>,
+    q_settings: Query<(), With<PauseSettingsPanel>>,
+) {
+    if !q_settings.is_empty() {
+        return;
+    }
+    let panel = commands
+        .spawn((
+            PauseSettingsPanel,
+            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.9)),
+            ZIndex(1),
+            ChildOf(*q_screen),
+        ))
+        .id();
+
+    for (sys_name, text) in [
+        ("toggle_fullscreen", "Toggle Fullscreen"),
+        ("close_pause_settings", "Back"),
+    ] {
+        let btn_ent = spawn_pause_button(&mut commands, &assets, &systems, sys_name, text);
+        commands.entity(btn_ent).insert(ChildOf(panel));
+    }
+}
+
+fn close_pause_settings(
+    mut commands: Commands,
+    q_settings: Query<Entity, With<PauseSettingsPanel>>,
+) {
+    for ent in &q_settings {
+        commands.entity(ent).despawn();
+    }
+}
+
+fn toggle_fullscreen(mut q_window: Single<&mut Window, With<PrimaryWindow>>) {
+    q_window.mode = match q_window.mode {
+        WindowMode::Windowed => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+        _ => WindowMode::Windowed,
+    };
+}
+
+/// Takes us all the way back to the main menu, from here or from the post game. In multiplayer,
+/// this means dropping our connection to the server, and in single player it's the end of the run.
+///
+/// The game objects themselves get cleaned up on exiting `AppState::InGame`
+fn leave_game(
+    mut commands: Commands,
+    mut game_kind: ResMut<CurrentGameKind>,
+    mut next_app: ResMut<NextState<AppState>>,
+    mut next_pause: ResMut<NextState<PauseState>>,
+    mut next_game: ResMut<NextState<InGameState>>,
+    q_client: Option<Single<Entity, With<Client>>>,
+) {
+    if let Some(c) = q_client {
+        commands.entity(*c).despawn();
+    }
+    game_kind.0 = None;
+    next_pause.set(PauseState::Unpaused);
+    next_game.set(InGameState::OutOfGame);
+    next_app.set(AppState::MainMenu);
+}
@@ -0,0 +1,80 @@
+//! The screen that comes up once a match is over, with how it went and a way back to the menu.
+//!
+//! Everything from the match is gone by the time this shows up (it gets cleaned up on leaving
+//! `AppState::InGame`), so all it has to go on is the `LastMatchOutcome`
+use bevy::prelude::*;
+
+use crate::{
+    render::ui::button::*,
+    shared::{
+        match_clock::{LastMatchOutcome, MatchOutcome},
+        states::AppState,
+    },
+};
+
+pub struct PostGameScreenPlugin;
+
+impl Plugin for PostGameScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::PostGame), spawn_post_game_screen);
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+#[require(Node = node_post_game_screen())]
+pub struct PostGameScreen;
+
+fn node_post_game_screen() -> Node {
+    Node {
+        height: Val::Percent(100.0),
+        width: Val::Percent(100.0),
+        display: Display::Flex,
+        flex_direction: FlexDirection::Column,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(32.0),
+        ..default()
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ButtonReturnToMenu;
+
+fn spawn_post_game_screen(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    systems: Res<ButtonSystems>,
+    outcome: Option<Res<LastMatchOutcome>>,
+) {
+    let title = match outcome.map(|o| o.0) {
+        Some(MatchOutcome::Victory) => "Victory!",
+        Some(MatchOutcome::Defeat) => "Defeat",
+        None => "Match Over",
+    };
+    let screen = commands
+        .spawn((
+            PostGameScreen,
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            DespawnOnExit(AppState::PostGame),
+        ))
+        .with_child((
+            Text::new(title),
+            TextFont {
+                font_size: 48.0,
+                ..default()
+            },
+        ))
+        .id();
+
+    // Leaving from here is the same as leaving a game from the pause menu
+    let sys = systems.get("leave_game").unwrap();
+    let button = GameButton::new(GameButtonOnRelease::TriggerSystem(*sys));
+    let style = GameButtonStyle::new(GameButtonImage::default())
+        .with_color(Color::srgb(1.0, 0.0, 0.0))
+        .with_size(Val::Percent(20.0), Val::Percent(10.0))
+        .with_text("Main Menu".into());
+    let btn_ent = button.spawn(&mut commands, &assets, style);
+    commands
+        .entity(btn_ent)
+        .insert((ButtonReturnToMenu, ChildOf(screen)));
+}
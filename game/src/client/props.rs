@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use crate::{
+    render::props::{rendering_on_pickup_add, rendering_on_prop_add},
+    shared::{
+        combat::CombatSystemSet,
+        drops::add_non_replicated_pickup_components,
+        game_kinds::{DefaultClientFilter, SinglePlayer, is_single_player},
+        loot::open_chests,
+        pickups::vacuum_gems,
+        props::*,
+        rng::MatchRng,
+        states::AppState,
+    },
+};
+
+/// Props are only spawned and broken open where the game is simulated, so in multiplayer the
+/// client just gets them from the server
+pub struct ClientPropsPlugin;
+
+impl Plugin for ClientPropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            spawn_map_props.run_if(is_single_player),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                update_prop_spawner::<With<SinglePlayer>>.run_if(resource_exists::<PropSpawner>),
+                vacuum_gems::<With<SinglePlayer>>,
+                open_chests::<With<SinglePlayer>>.run_if(resource_exists::<MatchRng>),
+            )
+                .in_set(CombatSystemSet::Combat),
+        )
+        .add_observer(add_non_replicated_prop_components::<DefaultClientFilter>)
+        .add_observer(add_non_replicated_pickup_components::<DefaultClientFilter>)
+        .add_observer(on_prop_death::<With<SinglePlayer>>);
+    }
+}
+
+pub struct ClientPropsRenderPlugin;
+impl Plugin for ClientPropsRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                rendering_on_prop_add::<Or<(With<SinglePlayer>, With<Predicted>)>>,
+                rendering_on_pickup_add::<Or<(With<SinglePlayer>, With<Predicted>)>>,
+            ),
+        );
+    }
+}
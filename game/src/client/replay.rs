@@ -0,0 +1,327 @@
+//! Recording single player matches to a file, and playing them back.
+//!
+//! Everything random in a match comes from the match seed, so the rules (which carry the seed)
+//! and the movement input on every tick are all it takes to play a match out again. A replay
+//! also keeps a snapshot of where the match ended up, and playback checks that it gets to the
+//! same place. If it doesn't, something in the simulation isn't deterministic.
+//!
+//! Recording gets turned on with `client --record <path>`, and a recording gets played back
+//! with `replay <path>`, which runs as fast as it can unless it's given `--render`
+use std::path::{Path, PathBuf};
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{lobby::client_on_receive_start_game_message, transition_to_single_player},
+    shared::{
+        combat::CombatSystemSet,
+        damage::Dead,
+        enemies::Enemy,
+        game_kinds::is_single_player,
+        game_object_spawning::despawn_game_objects,
+        game_rules::GameRules,
+        inputs::Movement,
+        lobby::ClientStartGameMessage,
+        players::{Gold, Player},
+        states::AppState,
+        stats::components::Health,
+    },
+};
+
+/// Records single player matches, as long as there's a `ReplayRecorder` to record to
+pub struct ClientReplayPlugin;
+
+impl Plugin for ClientReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            start_recording.run_if(resource_exists::<ReplayRecorder>.and(is_single_player)),
+        )
+        // Every tick that runs gets recorded, whatever `InGameState` says. Movement isn't gated
+        // on it, so a tick that sneaks in on the frame the game gets paused still counts
+        .add_systems(
+            FixedUpdate,
+            record_inputs
+                .run_if(recording)
+                .in_set(CombatSystemSet::PreCombat),
+        )
+        .add_systems(
+            OnExit(AppState::InGame),
+            save_recording
+                .run_if(recording)
+                .before(despawn_game_objects),
+        )
+        // Closing the game partway through a match still keeps what was recorded so far
+        .add_systems(
+            Last,
+            save_recording.run_if(recording.and(on_message::<AppExit>)),
+        );
+    }
+}
+
+/// Skips the menus and the lobby, and plays a recorded match back from its first tick
+pub struct ReplayPlaybackPlugin;
+
+impl Plugin for ReplayPlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, transition_to_single_player)
+            .add_systems(
+                Update,
+                start_replayed_match
+                    .run_if(in_state(AppState::Lobby))
+                    .before(client_on_receive_start_game_message),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    finish_playback.run_if(playback_reached_end),
+                    feed_replay_inputs,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame))
+                    .in_set(CombatSystemSet::PreCombat),
+            )
+            // The match can end before the inputs do, if the player died
+            .add_systems(
+                OnExit(AppState::InGame),
+                finish_playback
+                    .run_if(playback_unfinished)
+                    .before(despawn_game_objects),
+            );
+    }
+}
+
+/// The same movement input, held for a number of ticks in a row
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct InputRun {
+    pub ticks: u32,
+    pub movement: Vec2,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlayerSnapshot {
+    pub position: Vec2,
+    pub health: f32,
+    pub gold: u32,
+}
+
+/// Enough of the world to tell whether two runs of a match ended up in the same place
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplaySnapshot {
+    pub ticks: u32,
+    pub players: Vec<PlayerSnapshot>,
+    pub enemies: usize,
+}
+
+impl ReplaySnapshot {
+    pub fn take(world: &mut World, ticks: u32) -> Self {
+        let players = world
+            .query_filtered::<(&Position, &Health, &Gold), With<Player>>()
+            .iter(world)
+            .map(|(pos, health, gold)| PlayerSnapshot {
+                position: pos.0,
+                health: health.current,
+                gold: gold.0,
+            })
+            .collect();
+        let enemies = world
+            .query_filtered::<(), (With<Enemy>, Without<Dead>)>()
+            .iter(world)
+            .count();
+        Self {
+            ticks,
+            players,
+            enemies,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub rules: GameRules,
+    /// Movement for every tick of the match, with the ticks that had the same input as the
+    /// one before them run together
+    pub inputs: Vec<InputRun>,
+    /// Where the match was when the recording stopped
+    pub end: Option<ReplaySnapshot>,
+}
+
+impl ReplayFile {
+    pub fn new(rules: GameRules) -> Self {
+        Self {
+            rules,
+            inputs: Vec::new(),
+            end: None,
+        }
+    }
+
+    pub fn load(path: &Path) -> Self {
+        crate::utils::read_ron::<ReplayFile>(path.display().to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let s = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(std::io::Error::other)?;
+        std::fs::write(path, s)
+    }
+
+    pub fn push_input(&mut self, movement: Vec2) {
+        match self.inputs.last_mut() {
+            Some(run) if run.movement == movement => run.ticks += 1,
+            _ => self.inputs.push(InputRun { ticks: 1, movement }),
+        }
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.inputs.iter().map(|run| run.ticks).sum()
+    }
+
+    /// The movement for each tick, one after another
+    pub fn tick_inputs(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.inputs
+            .iter()
+            .flat_map(|run| std::iter::repeat_n(run.movement, run.ticks as usize))
+    }
+}
+
+/// Where single player matches get recorded to. Each match starts a new recording, so only
+/// the last match of the session ends up in the file
+#[derive(Resource, Debug)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    /// The match that's being recorded right now, if there is one
+    replay: Option<ReplayFile>,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, replay: None }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    pub replay: ReplayFile,
+    inputs: Vec<Vec2>,
+    pub tick: usize,
+    /// Headless playback quits once it's done, and rendered playback stops on the last tick so
+    /// that there's something to look at
+    pub exit_when_done: bool,
+    finished: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: ReplayFile, exit_when_done: bool) -> Self {
+        Self {
+            inputs: replay.tick_inputs().collect(),
+            replay,
+            tick: 0,
+            exit_when_done,
+            finished: false,
+        }
+    }
+}
+
+fn recording(recorder: Option<Res<ReplayRecorder>>) -> bool {
+    recorder.is_some_and(|r| r.replay.is_some())
+}
+
+fn start_recording(mut recorder: ResMut<ReplayRecorder>, rules: Res<GameRules>) {
+    info!("Recording the match to {}", recorder.path.display());
+    recorder.replay = Some(ReplayFile::new(*rules));
+}
+
+pub fn record_inputs(
+    mut recorder: ResMut<ReplayRecorder>,
+    q_movement: Query<&ActionValue, (With<Action<Movement>>, With<ActionOf<Player>>)>,
+) {
+    let movement = q_movement
+        .iter()
+        .next()
+        .map_or(Vec2::ZERO, |v| v.as_axis2d());
+    if let Some(replay) = &mut recorder.replay {
+        replay.push_input(movement);
+    }
+}
+
+fn save_recording(world: &mut World) {
+    let Some(mut replay) = world.resource_mut::<ReplayRecorder>().replay.take() else {
+        return;
+    };
+    replay.end = Some(ReplaySnapshot::take(world, replay.ticks()));
+    let path = world.resource::<ReplayRecorder>().path.clone();
+    match replay.save(&path) {
+        Ok(()) => info!("Saved {} ticks to {}", replay.ticks(), path.display()),
+        Err(e) => error!("Couldn't save the replay to {}: {e}", path.display()),
+    }
+}
+
+/// Plays the match with the recorded rules, rather than whatever the lobby came up with
+fn start_replayed_match(
+    playback: Res<ReplayPlayback>,
+    mut rules: ResMut<GameRules>,
+    mut messages: MessageWriter<ClientStartGameMessage>,
+) {
+    *rules = playback.replay.rules;
+    messages.write(ClientStartGameMessage);
+}
+
+fn playback_reached_end(playback: Res<ReplayPlayback>) -> bool {
+    !playback.finished && playback.tick >= playback.inputs.len()
+}
+
+fn playback_unfinished(playback: Option<Res<ReplayPlayback>>) -> bool {
+    playback.is_some_and(|p| !p.finished)
+}
+
+fn feed_replay_inputs(
+    mut playback: ResMut<ReplayPlayback>,
+    mut q_movement: Query<&mut ActionValue, (With<Action<Movement>>, With<ActionOf<Player>>)>,
+) {
+    let Some(movement) = playback.inputs.get(playback.tick).copied() else {
+        return;
+    };
+    for mut value in &mut q_movement {
+        *value = movement.into();
+    }
+    playback.tick += 1;
+}
+
+fn finish_playback(world: &mut World) {
+    let mut playback = world.resource_mut::<ReplayPlayback>();
+    playback.finished = true;
+    let ticks = playback.tick as u32;
+    let snapshot = ReplaySnapshot::take(world, ticks);
+    let playback = world.resource::<ReplayPlayback>();
+    let exit_when_done = playback.exit_when_done;
+    let matched = match &playback.replay.end {
+        Some(expected) if *expected == snapshot => {
+            info!("Replay finished after {ticks} ticks, in the same state as the recording");
+            true
+        }
+        Some(expected) => {
+            error!(
+                "Replay finished after {ticks} ticks, but didn't match the recording.\nExpected: {expected:?}\nFound: {snapshot:?}"
+            );
+            false
+        }
+        None => {
+            warn!("Replay finished after {ticks} ticks, but the recording has no end to check");
+            true
+        }
+    };
+
+    if exit_when_done {
+        world.write_message(if matched {
+            AppExit::Success
+        } else {
+            AppExit::error()
+        });
+    } else {
+        world.resource_mut::<Time<Virtual>>().pause();
+    }
+}
@@ -0,0 +1,339 @@
+//! Headless single player matches, played by a simple AI, for balancing.
+//!
+//! Each match gets built into its own app, skips the menus, and then runs one tick per update
+//! with nothing rendered, so it goes as fast as the machine allows. The pilot doesn't try to be
+//! good at the game: it runs from anything that gets close and picks up whatever XP is nearby,
+//! which is enough to tell whether a change to `stats.ron` made things easier or harder.
+//!
+//! `simulate --runs 10 --seed 0 --difficulty Hard` plays ten matches, with seeds 0 through 9,
+//! and prints a JSON summary of each one
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+use avian2d::prelude::*;
+use bevy::{app::PluginsState, ecs::query::QueryFilter, prelude::*, time::TimeUpdateStrategy};
+use bevy_enhanced_input::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    build::{TICKRATE, build_game_client_app},
+    client::{lobby::client_on_receive_start_game_message, transition_to_single_player},
+    shared::{
+        combat::CombatSystemSet,
+        damage::{AppliedDamageMessage, Dead, KilledBy, damage_chain, responsible_player},
+        drops::XPGem,
+        enemies::Enemy,
+        game_object_spawning::despawn_game_objects,
+        game_rules::{Difficulty, GameRules},
+        inputs::Movement,
+        lobby::ClientStartGameMessage,
+        map::CurrentMap,
+        match_clock::{MatchClock, MatchEndedMessage, MatchOutcome},
+        players::{CharacterKind, Player, PlayerLoadout},
+        states::{AppState, InGameState},
+        stats::xp::LevelManager,
+        weapons::{Weapon, WeaponKind},
+    },
+    utils::CreatedBy,
+};
+
+/// Enemies closer than this push the pilot away, harder the closer they are
+const PILOT_DANGER_RADIUS: f32 = 250.0;
+/// The pilot only goes after gems that are this close
+const PILOT_GEM_RADIUS: f32 = 400.0;
+/// The pilot starts turning back this far from the edge of the map
+const PILOT_EDGE_MARGIN: f32 = 150.0;
+/// Gives up on a match that somehow runs this many ticks past its length
+const OVERTIME_TICKS: u32 = 64 * 60;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SimulationConfig {
+    pub character: CharacterKind,
+    pub weapon: WeaponKind,
+    pub difficulty: Difficulty,
+    pub seed: u64,
+    /// In seconds. Leaving it out keeps the usual match length
+    pub match_length: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationSummary {
+    pub seed: u64,
+    pub character: CharacterKind,
+    pub weapon: WeaponKind,
+    pub difficulty: Difficulty,
+    /// Nothing here means the match had to be cut off
+    pub outcome: Option<MatchOutcome>,
+    /// Seconds until the player died, or until the match ended if they didn't
+    pub survival_time: f32,
+    pub kills: u32,
+    pub level: u8,
+    pub damage_by_weapon: BTreeMap<WeaponKind, f32>,
+}
+
+impl SimulationSummary {
+    fn new(config: &SimulationConfig) -> Self {
+        Self {
+            seed: config.seed,
+            character: config.character,
+            weapon: config.weapon,
+            difficulty: config.difficulty,
+            outcome: None,
+            survival_time: 0.0,
+            kills: 0,
+            level: 0,
+            damage_by_weapon: BTreeMap::new(),
+        }
+    }
+}
+
+/// The match that's being simulated, and how it's gone so far
+#[derive(Resource, Debug)]
+pub struct SimulationRun {
+    pub config: SimulationConfig,
+    pub summary: SimulationSummary,
+    died_at: Option<f32>,
+}
+
+impl SimulationRun {
+    pub fn new(config: SimulationConfig) -> Self {
+        Self {
+            summary: SimulationSummary::new(&config),
+            config,
+            died_at: None,
+        }
+    }
+}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, transition_to_single_player)
+            .add_systems(
+                Update,
+                start_simulated_match
+                    .run_if(in_state(AppState::Lobby))
+                    .before(client_on_receive_start_game_message),
+            )
+            .add_systems(
+                FixedUpdate,
+                pilot_player
+                    .run_if(in_state(InGameState::InGame))
+                    .in_set(CombatSystemSet::PreCombat),
+            )
+            .add_systems(FixedPostUpdate, tally_damage.in_set(CombatSystemSet::Last))
+            .add_systems(Update, record_outcome.run_if(in_state(AppState::InGame)))
+            .add_systems(
+                OnExit(AppState::InGame),
+                finish_simulated_match.before(despawn_game_objects),
+            )
+            .add_observer(count_deaths);
+    }
+}
+
+/// Plays every match one after the other, and prints each summary as a line of JSON as soon as
+/// it's done. With `out`, they all get written there as well once every match is over
+pub fn run_simulations(
+    config: SimulationConfig,
+    runs: u64,
+    out: Option<&Path>,
+) -> Vec<SimulationSummary> {
+    let summaries: Vec<SimulationSummary> = (0..runs)
+        .map(|i| {
+            let summary = simulate_match(SimulationConfig {
+                seed: config.seed.wrapping_add(i),
+                ..config
+            });
+            match serde_json::to_string(&summary) {
+                Ok(json) => println!("{json}"),
+                Err(e) => error!("Couldn't write the summary: {e}"),
+            }
+            summary
+        })
+        .collect();
+
+    if let Some(path) = out {
+        let written = serde_json::to_string_pretty(&summaries)
+            .map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(path, json));
+        if let Err(e) = written {
+            error!("Couldn't write the summaries to {}: {e}", path.display());
+        }
+    }
+    summaries
+}
+
+/// Builds a fresh app for the match, and runs it until the match is over
+pub fn simulate_match(config: SimulationConfig) -> SimulationSummary {
+    let mut app = App::new();
+    build_game_client_app(&mut app, None, false);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        TICKRATE,
+    )))
+    .insert_resource(PlayerLoadout {
+        character: config.character,
+        weapon: config.weapon,
+    })
+    .insert_resource(SimulationRun::new(config))
+    .add_plugins(SimulationPlugin);
+
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let match_length = config
+        .match_length
+        .unwrap_or(GameRules::default().match_length);
+    let max_updates = (match_length as f64 / TICKRATE) as u32 + OVERTIME_TICKS;
+    for _ in 0..max_updates {
+        app.update();
+        if app.should_exit().is_some() {
+            break;
+        }
+    }
+
+    let run = app
+        .world_mut()
+        .remove_resource::<SimulationRun>()
+        .expect("The simulation run should be there for the whole match");
+    if run.summary.outcome.is_none() {
+        warn!("Match {} didn't finish, so it was cut off", config.seed);
+    }
+    run.summary
+}
+
+/// Plays with the simulation's rules, rather than whatever the lobby came up with
+fn start_simulated_match(
+    run: Res<SimulationRun>,
+    mut rules: ResMut<GameRules>,
+    mut messages: MessageWriter<ClientStartGameMessage>,
+) {
+    rules.seed = run.config.seed;
+    rules.difficulty = run.config.difficulty;
+    if let Some(length) = run.config.match_length {
+        rules.match_length = length;
+    }
+    messages.write(ClientStartGameMessage);
+}
+
+/// Runs from the enemies that are close, goes for the gems that are close, and stays away from
+/// the edge of the map
+fn pilot_player(
+    map: Res<CurrentMap>,
+    q_player: Query<&Position, (With<Player>, Without<Dead>)>,
+    q_enemies: Query<&Position, (With<Enemy>, Without<Dead>)>,
+    q_gems: Query<&Position, With<XPGem>>,
+    mut q_movement: Query<&mut ActionValue, (With<Action<Movement>>, With<ActionOf<Player>>)>,
+) {
+    let Some(pos) = q_player.iter().next().map(|p| p.0) else {
+        return;
+    };
+
+    let mut flee = Vec2::ZERO;
+    for enemy in &q_enemies {
+        let away = pos - enemy.0;
+        let dist = away.length();
+        if dist < PILOT_DANGER_RADIUS && dist > f32::EPSILON {
+            flee += away / dist * (1.0 - dist / PILOT_DANGER_RADIUS);
+        }
+    }
+
+    let nearest_gem = q_gems
+        .iter()
+        .map(|g| g.0 - pos)
+        .filter(|to| to.length() < PILOT_GEM_RADIUS)
+        .min_by(|a, b| a.length().total_cmp(&b.length()));
+    // Greed only wins out once nothing is close
+    let greed = nearest_gem.map_or(Vec2::ZERO, |to| to.normalize_or_zero() * 0.5);
+
+    let bounds = map.bounds;
+    let mut inward = Vec2::ZERO;
+    if pos.x - bounds.min.x < PILOT_EDGE_MARGIN {
+        inward.x += 1.0;
+    }
+    if bounds.max.x - pos.x < PILOT_EDGE_MARGIN {
+        inward.x -= 1.0;
+    }
+    if pos.y - bounds.min.y < PILOT_EDGE_MARGIN {
+        inward.y += 1.0;
+    }
+    if bounds.max.y - pos.y < PILOT_EDGE_MARGIN {
+        inward.y -= 1.0;
+    }
+
+    let movement = (flee * 2.0 + greed + inward).normalize_or_zero();
+    for mut value in &mut q_movement {
+        *value = movement.into();
+    }
+}
+
+/// Which weapon (if any) some damage came from, going from a projectile to the weapon that made
+/// it the same way that `responsible_player` does
+fn weapon_of<F: QueryFilter>(
+    source: Entity,
+    q_created: &Query<&CreatedBy>,
+    q_parent: &Query<&ChildOf>,
+    q_weapon: &Query<&Weapon, F>,
+) -> Option<WeaponKind> {
+    damage_chain(
+        source,
+        |ent| q_created.get(ent).ok().map(|c| c.0),
+        |ent| q_parent.get(ent).ok().map(|p| p.parent()),
+    )
+    .into_iter()
+    .find_map(|ent| q_weapon.get(ent).ok())
+    .map(|w| w.kind())
+}
+
+fn tally_damage(
+    mut run: ResMut<SimulationRun>,
+    mut damage: MessageReader<AppliedDamageMessage>,
+    q_created: Query<&CreatedBy>,
+    q_parent: Query<&ChildOf>,
+    q_weapon: Query<&Weapon>,
+) {
+    for hit in damage.read() {
+        if let Some(kind) = weapon_of(hit.source, &q_created, &q_parent, &q_weapon) {
+            *run.summary.damage_by_weapon.entry(kind).or_default() += hit.amount;
+        }
+    }
+}
+
+/// Counts the enemies that the player killed, and notes when the player died
+fn count_deaths(
+    trigger: On<Add, Dead>,
+    mut run: ResMut<SimulationRun>,
+    q_enemy: Query<&KilledBy, With<Enemy>>,
+    q_clock: Query<&MatchClock>,
+    q_created: Query<&CreatedBy>,
+    q_parent: Query<&ChildOf>,
+    q_player: Query<(), With<Player>>,
+) {
+    if let Ok(killer) = q_enemy.get(trigger.entity) {
+        if responsible_player(killer.0, &q_created, &q_parent, &q_player).is_some() {
+            run.summary.kills += 1;
+        }
+    } else if q_player.contains(trigger.entity) && run.died_at.is_none() {
+        run.died_at = q_clock.iter().next().map(|c| c.elapsed);
+    }
+}
+
+fn record_outcome(mut run: ResMut<SimulationRun>, mut ended: MessageReader<MatchEndedMessage>) {
+    if let Some(m) = ended.read().last() {
+        run.summary.outcome = Some(m.outcome);
+    }
+}
+
+fn finish_simulated_match(
+    mut run: ResMut<SimulationRun>,
+    mut exit: MessageWriter<AppExit>,
+    q_clock: Query<&MatchClock>,
+    q_levels: Query<&LevelManager>,
+) {
+    let elapsed = q_clock.iter().next().map_or(0.0, |c| c.elapsed);
+    run.summary.survival_time = run.died_at.unwrap_or(elapsed);
+    run.summary.level = q_levels.iter().map(|l| l.c_level).max().unwrap_or(0);
+    exit.write(AppExit::Success);
+}
@@ -6,15 +6,19 @@ use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
 pub mod animation;
 pub mod camera;
+pub mod drops;
 pub mod enemies;
+pub mod hit_feedback;
 pub mod map;
 pub mod menus;
 pub mod player;
+pub mod props;
 pub mod ui;
 pub mod weapons;
 
 use camera::GameMainCamera;
 use enemies::SharedEnemyRenderPlugin;
+use hit_feedback::HitFeedbackPlugin;
 use map::MapRenderPlugin;
 use menus::lobby::LobbyMenuPlugin;
 use player::SharedPlayerRenderPlugin;
@@ -33,6 +37,7 @@ impl Plugin for GameSharedRenderPlugin {
             MapRenderPlugin,
             SharedPlayerRenderPlugin,
             SharedEnemyRenderPlugin,
+            HitFeedbackPlugin,
         ));
         #[cfg(feature = "inspector")]
         app.add_plugins((EguiPlugin::default(), WorldInspectorPlugin::new()));
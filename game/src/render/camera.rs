@@ -1,8 +1,13 @@
-use bevy::prelude::*;
+use bevy::{input::mouse::MouseWheel, prelude::*};
 use lightyear::prelude::Controlled;
 
 use crate::shared::{game_kinds::SinglePlayer, players::Player};
 
+/// How fast the free camera pans, in world units per second when it isn't zoomed
+pub const FREE_CAMERA_SPEED: f32 = 800.0;
+/// How much one notch of the scroll wheel zooms the free camera
+const FREE_CAMERA_ZOOM_STEP: f32 = 1.1;
+
 pub struct GameCameraPlugin;
 
 impl Plugin for GameCameraPlugin {
@@ -47,3 +52,38 @@ pub fn update_camera_pos_client(
         _ => {}
     }
 }
+
+/// Pans the camera around with WASD and zooms it with the scroll wheel, while it's in free cam
+pub fn move_free_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut wheel: MessageReader<MouseWheel>,
+    q_camera: Single<(&mut Transform, &mut Projection, &GameMainCamera)>,
+) {
+    let (mut transform, mut projection, camera) = q_camera.into_inner();
+    if !matches!(camera.mode, GameCameraMode::FreeCam) {
+        wheel.clear();
+        return;
+    }
+    let Projection::Orthographic(ortho) = &mut *projection else {
+        return;
+    };
+    for scroll in wheel.read() {
+        ortho.scale = (ortho.scale * FREE_CAMERA_ZOOM_STEP.powf(-scroll.y)).clamp(0.25, 8.0);
+    }
+
+    let dir = [
+        (KeyCode::KeyW, Vec2::Y),
+        (KeyCode::KeyS, Vec2::NEG_Y),
+        (KeyCode::KeyA, Vec2::NEG_X),
+        (KeyCode::KeyD, Vec2::X),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keys.pressed(*key))
+    .map(|(_, dir)| dir)
+    .sum::<Vec2>()
+    .normalize_or_zero();
+    // Zoomed out, the camera should cover the same amount of the screen per second
+    let step = dir * FREE_CAMERA_SPEED * ortho.scale * time.delta_secs();
+    transform.translation += step.extend(0.0);
+}
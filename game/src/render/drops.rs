@@ -0,0 +1,28 @@
+use crate::{render::RenderYtoZ, shared::drops::XPGem};
+use avian2d::prelude::Position;
+use bevy::{ecs::query::QueryFilter, prelude::*};
+
+const XP_GEM_COLOR: Color = Color::srgb(0.3, 0.6, 1.0);
+const XP_GEM_SIZE: f32 = 10.0;
+/// Gems worth at least this much (like a boss's reward) stand out from the rest
+const BIG_XP_GEM: f32 = 50.0;
+const BIG_XP_GEM_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const BIG_XP_GEM_SIZE: f32 = 24.0;
+
+pub fn rendering_on_xp_gem_add<QF: QueryFilter>(
+    mut commands: Commands,
+    q_gem: Query<(Entity, &XPGem, &Position), (Added<XPGem>, QF)>,
+) {
+    for (e, gem, pos) in &q_gem {
+        let (color, size) = if gem.amount >= BIG_XP_GEM {
+            (BIG_XP_GEM_COLOR, BIG_XP_GEM_SIZE)
+        } else {
+            (XP_GEM_COLOR, XP_GEM_SIZE)
+        };
+        commands.entity(e).insert((
+            Sprite::from_color(color, Vec2::splat(size)),
+            Transform::from_translation(pos.0.extend(pos.0.y)),
+            RenderYtoZ,
+        ));
+    }
+}
@@ -1,58 +1,104 @@
-use crate::{render::RenderYtoZ, shared::enemies::*};
+use crate::{
+    render::{RenderYtoZ, animation::*},
+    shared::{
+        enemies::{definitions::EnemyRegistry, elites::Elite, *},
+        projectiles::{Projectile, ProjectileFaction},
+    },
+};
 use avian2d::prelude::Position;
-use bevy::{ecs::query::QueryFilter, prelude::*};
-use core::marker::PhantomData;
+use bevy::{ecs::query::QueryFilter, prelude::*, render::RenderSystems};
 
 pub struct SharedEnemyRenderPlugin;
 
 impl Plugin for SharedEnemyRenderPlugin {
     fn build(&self, app: &mut App) {
-        /*
         app.add_systems(
             Update,
-            (
-                                    (animate::<Player>, update_facing_direction::<Player>)
-                                        .chain()
-                                        .before(RenderSystems::ExtractCommands)
-            ),
-
+            ((animate::<Enemy>, update_facing_direction::<Enemy>)
+                .chain()
+                .before(RenderSystems::ExtractCommands),),
         );
-        */
     }
 }
 
+/// Elites are tinted and drawn a bit bigger, so they stand out in a crowd
+const ELITE_TINT: Color = Color::srgb(1.0, 0.55, 0.3);
+const ELITE_SCALE: f32 = 1.25;
+
+/// Sets up the sprite from the enemy's definition. Enemies with an animation get a texture
+/// atlas, and face the way that they're moving
 pub fn rendering_on_enemy_add<QF: QueryFilter>(
     mut commands: Commands,
     assets: Res<AssetServer>,
+    registry: Res<EnemyRegistry>,
     mut layouts: ResMut<Assets<TextureAtlasLayout>>,
-    q_enemy: Query<(Entity, &Position), (Added<Enemy>, QF)>,
+    q_enemy: Query<(Entity, &Enemy, &Position, Has<Elite>), (Added<Enemy>, QF)>,
 ) {
-    for (e, pos) in &q_enemy {
-        let handle: Handle<Image> = assets.load("enemies/faceless/sprite.png");
-        //let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 4, 4, None, None);
-        //let tex_atlas = layouts.add(layout);
-        /*
-        let animation = AnimationConfig::new(0, 3, 4);
-
-        let facing = AnimationFacing {
-            tex_width: 4,
-            ..default()
+    for (e, enemy, pos, is_elite) in &q_enemy {
+        let def = registry.get(enemy.kind);
+        let handle: Handle<Image> = assets.load(
+            registry
+                .folder(enemy.kind)
+                .to_path(def.sprite.image.clone()),
+        );
+        let (color, scale) = if is_elite {
+            (ELITE_TINT, ELITE_SCALE)
+        } else {
+            (Color::WHITE, 1.0)
         };
-        */
         commands.entity(e).insert((
-            Sprite::from_image(handle),
-            /*
-            Sprite {
-                image: handle,
-                texture_atlas: Some(TextureAtlas {
-                    layout: tex_atlas.clone(),
-                    index: 0,
-                }),
-                ..default()
-            },
-            facing,
-            animation,
-            */
+            Transform::from_translation(pos.0.extend(pos.0.y)).with_scale(Vec3::splat(scale)),
+            RenderYtoZ,
+        ));
+        match def.sprite.animation {
+            Some(anim) => {
+                let layout = TextureAtlasLayout::from_grid(
+                    anim.tile_size,
+                    anim.columns,
+                    anim.rows,
+                    None,
+                    None,
+                );
+                let tex_atlas = layouts.add(layout);
+                commands.entity(e).insert((
+                    Sprite {
+                        image: handle,
+                        texture_atlas: Some(TextureAtlas {
+                            layout: tex_atlas,
+                            index: 0,
+                        }),
+                        color,
+                        ..default()
+                    },
+                    AnimationConfig::new(0, anim.columns as usize - 1, anim.fps),
+                    AnimationFacing {
+                        tex_width: anim.columns,
+                        ..default()
+                    },
+                ));
+            }
+            None => {
+                commands.entity(e).insert(Sprite {
+                    color,
+                    ..Sprite::from_image(handle)
+                });
+            }
+        }
+    }
+}
+
+const ENEMY_PROJECTILE_COLOR: Color = Color::srgb(0.8, 0.1, 0.6);
+
+pub fn rendering_on_enemy_projectile_add<QF: QueryFilter>(
+    mut commands: Commands,
+    q_projectile: Query<(Entity, &Projectile, &Position), (Added<Projectile>, QF)>,
+) {
+    for (e, proj, pos) in &q_projectile {
+        if proj.faction != ProjectileFaction::Enemy {
+            continue;
+        }
+        commands.entity(e).insert((
+            Sprite::from_color(ENEMY_PROJECTILE_COLOR, Vec2::splat(12.0)),
             Transform::from_translation(pos.0.extend(pos.0.y)),
             RenderYtoZ,
         ));
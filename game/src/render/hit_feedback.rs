@@ -0,0 +1,184 @@
+//! Visual feedback for damage landing: floating numbers and a short flash on whatever got hit
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use lightyear::prelude::Tick;
+
+use crate::{
+    render::ui::FadeEffect,
+    shared::{
+        damage::AppliedDamageMessage,
+        despawn_timer::DespawnTimer,
+        game_kinds::{CurrentGameKind, GameKinds},
+        players::Player,
+    },
+};
+
+const DAMAGE_NUMBER_LIFETIME: f32 = 0.8;
+const DAMAGE_NUMBER_RISE_SPEED: f32 = 40.0;
+const HIT_FLASH_TIME: f32 = 0.1;
+const HIT_FLASH_COLOR: Color = Color::srgb(1.0, 0.4, 0.4);
+/// How long (in seconds) a hit is remembered for, which is longer than any rollback goes back
+const DAMAGE_HISTORY_WINDOW: f32 = 1.0;
+
+pub struct HitFeedbackPlugin;
+
+impl Plugin for HitFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageNumberHistory>().add_systems(
+            Update,
+            (
+                spawn_damage_feedback,
+                float_damage_numbers,
+                update_hit_flash,
+            ),
+        );
+    }
+}
+
+/// Remembers which hits we've recently shown damage for, on a multiplayer client.
+///
+/// When the client rolls back, the tick gets replayed and the same hit gets written again, on the
+/// same tick as the first time. So a hit with the same tick, target and source as one that's
+/// already been shown is a replay rather than a new hit, and we don't show it. Nothing rolls back
+/// in single player, so every hit there gets shown
+#[derive(Resource, Default, Debug)]
+pub struct DamageNumberHistory {
+    shown_at: HashMap<(Tick, Entity, Entity), f32>,
+}
+
+impl DamageNumberHistory {
+    /// Returns true if this hit has not been shown yet, and records it
+    fn record(&mut self, tick: Tick, target: Entity, source: Entity, now: f32) -> bool {
+        self.shown_at
+            .retain(|_, at| now - *at < DAMAGE_HISTORY_WINDOW);
+        self.shown_at.insert((tick, target, source), now).is_none()
+    }
+}
+
+/// A number floating up off of something that got hit
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DamageNumber;
+
+/// Tints a sprite for a moment after it gets hit, then puts the color back
+#[derive(Component, Debug, Clone)]
+pub struct HitFlash {
+    pub timer: Timer,
+    pub base_color: Color,
+}
+
+/// How a given damage number should look
+enum DamageNumberStyle {
+    Normal,
+    Crit,
+    PlayerTaken,
+}
+
+impl DamageNumberStyle {
+    fn color(&self) -> Color {
+        match self {
+            Self::Normal => Color::WHITE,
+            Self::Crit => Color::srgb(1.0, 0.85, 0.1),
+            Self::PlayerTaken => Color::srgb(1.0, 0.2, 0.2),
+        }
+    }
+
+    fn font_size(&self) -> f32 {
+        match self {
+            Self::Normal | Self::PlayerTaken => 16.0,
+            Self::Crit => 24.0,
+        }
+    }
+}
+
+fn spawn_damage_feedback(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_kind: Res<CurrentGameKind>,
+    mut history: ResMut<DamageNumberHistory>,
+    mut messages: MessageReader<AppliedDamageMessage>,
+    mut q_target: Query<(
+        &Transform,
+        Has<Player>,
+        Option<&mut Sprite>,
+        Option<&mut HitFlash>,
+    )>,
+) {
+    let now = time.elapsed_secs();
+    let rolls_back = matches!(game_kind.0, Some(GameKinds::MultiPlayer));
+    // The flash gets inserted with commands, so we have to track what we've flashed this frame
+    // ourselves, or a second hit would record the flash color as the base color
+    let mut flashed = HashSet::new();
+    for hit in messages.read() {
+        if let Some(tick) = hit.tick
+            && rolls_back
+            && !history.record(tick, hit.target, hit.source, now)
+        {
+            continue;
+        }
+        let Ok((transform, is_player, m_sprite, m_flash)) = q_target.get_mut(hit.target) else {
+            continue;
+        };
+        let style = if is_player {
+            DamageNumberStyle::PlayerTaken
+        } else if hit.crit {
+            DamageNumberStyle::Crit
+        } else {
+            DamageNumberStyle::Normal
+        };
+        let text = if hit.crit {
+            format!("{:.0}!", hit.amount)
+        } else {
+            format!("{:.0}", hit.amount)
+        };
+        commands.spawn((
+            DamageNumber,
+            Text2d::new(text),
+            TextFont {
+                font_size: style.font_size(),
+                ..default()
+            },
+            TextColor(style.color()),
+            // Always draw on top of the sprites
+            Transform::from_translation(
+                (transform.translation.xy() + Vec2::new(0.0, 20.0)).extend(10.0),
+            ),
+            FadeEffect::fade_out(DAMAGE_NUMBER_LIFETIME, EaseFunction::QuadraticIn),
+            DespawnTimer::new(DAMAGE_NUMBER_LIFETIME),
+        ));
+
+        match (m_sprite, m_flash) {
+            (Some(_), Some(mut flash)) => flash.timer.reset(),
+            (Some(mut sprite), None) if flashed.insert(hit.target) => {
+                let base_color = sprite.color;
+                sprite.color = HIT_FLASH_COLOR;
+                commands.entity(hit.target).insert(HitFlash {
+                    timer: Timer::from_seconds(HIT_FLASH_TIME, TimerMode::Once),
+                    base_color,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn float_damage_numbers(time: Res<Time>, mut q_numbers: Query<&mut Transform, With<DamageNumber>>) {
+    for mut transform in &mut q_numbers {
+        transform.translation.y += DAMAGE_NUMBER_RISE_SPEED * time.delta_secs();
+    }
+}
+
+fn update_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_flash: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+) {
+    for (ent, mut flash, mut sprite) in &mut q_flash {
+        flash.timer.tick(time.delta());
+        if flash.timer.is_finished() {
+            sprite.color = flash.base_color;
+            commands.entity(ent).remove::<HitFlash>();
+        }
+    }
+}
@@ -1,15 +1,33 @@
 //! Responsible for handling render elmeents related to the game's map
 //!
-//!
-use bevy::prelude::*;
+//! The background is streamed in around the camera a chunk at a time, so it costs the same to
+//! draw however big the map is. Every chunk is a single tiled sprite, rather than a sprite per tile
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    render::camera::GameMainCamera,
+    shared::{
+        map::{CurrentMap, ObstacleShape, load_map},
+        states::AppState,
+    },
+};
 
-use crate::shared::{game_rules::GameRules, states::AppState};
+/// The size of the background texture, in world units
+pub const TILE_SIZE: f32 = 128.0;
+/// How many tiles go across (and down) a chunk
+pub const CHUNK_TILES: u32 = 8;
+pub const CHUNK_SIZE: f32 = TILE_SIZE * CHUNK_TILES as f32;
+/// How many extra chunks are kept loaded around the edge of the camera's view, so that the
+/// camera moving a little never shows a gap
+pub const CHUNK_MARGIN: i32 = 1;
 
 /// The map's rendered elements will work off of chunks so that I can spawn and despawn things somewhat easily.
 /// I'm anticipating some eventual first party support for this kind of thing, so the goal here is to keep this
 /// implementation simple
 #[derive(Component, Debug, Clone, Copy)]
-pub struct MapChunk;
+pub struct MapChunk {
+    pub coord: IVec2,
+}
 
 #[derive(Component)]
 pub struct MapBackground;
@@ -22,37 +40,157 @@ pub struct ChunkOf(Entity);
 #[relationship_target(relationship = ChunkOf)]
 pub struct HasChunks(Vec<Entity>);
 
+/// Keeps track of which chunks are around, so streaming doesn't have to go looking for them
+#[derive(Resource, Debug)]
+pub struct StreamedChunks {
+    pub background: Entity,
+    pub texture: Handle<Image>,
+    /// The part of the world that gets a background. `None` goes on forever
+    pub bounds: Option<Rect>,
+    pub loaded: HashMap<IVec2, Entity>,
+}
+
 pub struct MapRenderPlugin;
 
 impl Plugin for MapRenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::LoadingLevel), load_map_chunks);
+        app.add_systems(
+            OnEnter(AppState::LoadingLevel),
+            (load_map_chunks, render_obstacles).after(load_map),
+        )
+        .add_systems(
+            Update,
+            stream_map_chunks.run_if(resource_exists::<StreamedChunks>),
+        )
+        .add_systems(OnExit(AppState::InGame), |mut commands: Commands| {
+            commands.remove_resource::<StreamedChunks>();
+        });
     }
 }
 
-fn load_map_chunks(mut commands: Commands, _rules: Res<GameRules>, assets: Res<AssetServer>) {
-    // Making the map somewhat huge to start
-    let tiles = Vec2::new(64.0, 64.0);
-    let texture_size = Vec2::new(128.0, 128.0);
-    let map = commands
-        .spawn((MapBackground, Transform::default(), Visibility::Visible))
+pub fn chunk_coord(pos: Vec2) -> IVec2 {
+    (pos / CHUNK_SIZE).floor().as_ivec2()
+}
+
+pub fn chunk_rect(coord: IVec2) -> Rect {
+    let min = coord.as_vec2() * CHUNK_SIZE;
+    Rect::from_corners(min, min + Vec2::splat(CHUNK_SIZE))
+}
+
+/// Every chunk that should be loaded for the camera to see all of `view`, clipped to `bounds`
+pub fn chunks_in_view(view: Rect, margin: i32, bounds: Option<Rect>) -> Vec<IVec2> {
+    let mut min = chunk_coord(view.min) - IVec2::splat(margin);
+    let mut max = chunk_coord(view.max) + IVec2::splat(margin);
+    if let Some(bounds) = bounds {
+        // The max corner sits on the edge of the last chunk, so it doesn't count as being in
+        // the next one over
+        min = min.max(chunk_coord(bounds.min));
+        max = max.min(chunk_coord(bounds.max - Vec2::splat(0.001)));
+    }
+    let mut chunks = Vec::new();
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            chunks.push(IVec2::new(x, y));
+        }
+    }
+    chunks
+}
+
+fn load_map_chunks(mut commands: Commands, map: Res<CurrentMap>, assets: Res<AssetServer>) {
+    let background = commands
+        .spawn((
+            MapBackground,
+            Name::from("Map Background"),
+            Transform::default(),
+            Visibility::Visible,
+            DespawnOnExit(AppState::InGame),
+        ))
         .id();
+    commands.insert_resource(StreamedChunks {
+        background,
+        texture: assets.load("maps/grass_bg.png"),
+        bounds: (!map.endless_background).then_some(map.bounds),
+        loaded: HashMap::default(),
+    });
+}
 
-    let (total_size_x, total_size_y) = (tiles.x * texture_size.x, tiles.y * texture_size.y);
-    for x in (0..tiles.x as u32) {
-        for y in (0..tiles.y as u32) {
-            let texture: Handle<Image> = assets.load("maps/grass_bg.png");
-            commands.spawn((
-                Sprite::from(texture),
-                Transform::from_translation(Vec3::new(
-                    x as f32 * texture_size.x - (total_size_x / 2.0),
-                    y as f32 * texture_size.y - (total_size_y / 2.0),
-                    -1000.0,
-                )),
-                ChunkOf(map),
-                // this is for bevy inspector egui reasons
-                ChildOf(map),
-            ));
+/// Spawns the chunks that have come into view and despawns the ones that have gone out of it.
+/// Chunks only go away once they're a chunk further out than the ones being loaded, so the
+/// camera sitting on a boundary doesn't keep spawning and despawning the same chunk
+fn stream_map_chunks(
+    mut commands: Commands,
+    mut streamed: ResMut<StreamedChunks>,
+    camera: Single<(&Camera, &GlobalTransform), With<GameMainCamera>>,
+) {
+    let (camera, cam_transform) = *camera;
+    let Some(viewport) = camera.logical_viewport_rect() else {
+        return;
+    };
+    let corners =
+        [viewport.min, viewport.max].map(|c| camera.viewport_to_world_2d(cam_transform, c));
+    let [Ok(a), Ok(b)] = corners else {
+        return;
+    };
+    let view = Rect::from_corners(a, b);
+
+    let keep = chunks_in_view(view, CHUNK_MARGIN + 1, streamed.bounds);
+    streamed.loaded.retain(|coord, chunk| {
+        let keeping = keep.contains(coord);
+        if !keeping {
+            commands.entity(*chunk).despawn();
         }
+        keeping
+    });
+
+    for coord in chunks_in_view(view, CHUNK_MARGIN, streamed.bounds) {
+        if streamed.loaded.contains_key(&coord) {
+            continue;
+        }
+        let rect = match streamed.bounds {
+            Some(bounds) => chunk_rect(coord).intersect(bounds),
+            None => chunk_rect(coord),
+        };
+        let chunk = commands
+            .spawn((
+                MapChunk { coord },
+                Sprite {
+                    custom_size: Some(rect.size()),
+                    image_mode: SpriteImageMode::Tiled {
+                        tile_x: true,
+                        tile_y: true,
+                        stretch_value: 1.0,
+                    },
+                    ..Sprite::from_image(streamed.texture.clone())
+                },
+                Transform::from_translation(rect.center().extend(-1000.0)),
+                ChunkOf(streamed.background),
+                // this is for bevy inspector egui reasons
+                ChildOf(streamed.background),
+            ))
+            .id();
+        streamed.loaded.insert(coord, chunk);
+    }
+}
+
+/// The obstacles themselves are spawned by the shared map loading, this just gives them a look
+fn render_obstacles(
+    mut commands: Commands,
+    map: Res<CurrentMap>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let material = materials.add(Color::srgb(0.35, 0.3, 0.25));
+    for obstacle in &map.obstacles {
+        let mesh = match obstacle.shape {
+            ObstacleShape::Rectangle { width, height } => meshes.add(Rectangle::new(width, height)),
+            ObstacleShape::Circle { radius } => meshes.add(Circle::new(radius)),
+        };
+        commands.spawn((
+            Name::from("Obstacle Render"),
+            Mesh2d(mesh),
+            MeshMaterial2d(material.clone()),
+            Transform::from_translation(obstacle.center.extend(-900.0)),
+            DespawnOnExit(AppState::InGame),
+        ));
     }
 }
@@ -3,7 +3,7 @@ use bevy::prelude::*;
 use crate::{
     render::ui::button::*,
     shared::{
-        game_rules::{Difficulty, GameRuleField, MapKind},
+        game_rules::{Difficulty, GameRuleField, MapKind, XPSharing},
         states::AppState,
     },
     utils::CallbackWithInput,
@@ -15,7 +15,8 @@ impl Plugin for LobbyMenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Lobby), make_lobby)
             .add_observer(trigger_game_change_message_callback::<Difficulty>)
-            .add_observer(trigger_game_change_message_callback::<MapKind>);
+            .add_observer(trigger_game_change_message_callback::<MapKind>)
+            .add_observer(trigger_game_change_message_callback::<XPSharing>);
     }
 }
 
@@ -53,6 +54,19 @@ fn make_lobby(mut commands: Commands, assets: Res<AssetServer>) {
             .entity(btn_ent)
             .insert((ChangeGameSettingButton(*diff), ChildOf(lobby), cb));
     }
+
+    for sharing in [XPSharing::Shared, XPSharing::PerPlayer].iter() {
+        let button = GameButton::new(GameButtonOnRelease::EventTrigger);
+        let style = GameButtonStyle::default().with_text(format!("{:?} XP", sharing));
+        let system =
+            commands.register_system(crate::shared::game_rules::send_game_change_message_callback);
+        let cb = CallbackWithInput::<In<XPSharing>>(system);
+
+        let btn_ent = button.spawn(&mut commands, &assets, style);
+        commands
+            .entity(btn_ent)
+            .insert((ChangeGameSettingButton(*sharing), ChildOf(lobby), cb));
+    }
 }
 
 fn trigger_game_change_message_callback<F: GameRuleField>(
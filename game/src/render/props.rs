@@ -0,0 +1,55 @@
+use crate::{
+    render::RenderYtoZ,
+    shared::{
+        drops::{Pickup, PickupKind},
+        props::{Prop, PropKind},
+    },
+};
+use avian2d::prelude::Position;
+use bevy::{ecs::query::QueryFilter, prelude::*};
+
+const PICKUP_SIZE: f32 = 16.0;
+
+fn prop_color(kind: PropKind) -> Color {
+    match kind {
+        PropKind::Cooler => Color::srgb(0.2, 0.5, 0.9),
+        PropKind::Table => Color::srgb(0.55, 0.35, 0.2),
+    }
+}
+
+fn pickup_color(kind: PickupKind) -> Color {
+    match kind {
+        PickupKind::Heal { .. } => Color::srgb(0.9, 0.2, 0.25),
+        PickupKind::Magnet => Color::srgb(0.7, 0.7, 0.75),
+        PickupKind::Bomb { .. } => Color::srgb(0.15, 0.15, 0.15),
+        PickupKind::Freeze { .. } => Color::srgb(0.6, 0.9, 1.0),
+        PickupKind::Gold { .. } => Color::srgb(1.0, 0.85, 0.1),
+        PickupKind::Chest => Color::srgb(0.6, 0.4, 0.1),
+    }
+}
+
+pub fn rendering_on_prop_add<QF: QueryFilter>(
+    mut commands: Commands,
+    q_prop: Query<(Entity, &Prop, &Position), (Added<Prop>, QF)>,
+) {
+    for (e, prop, pos) in &q_prop {
+        commands.entity(e).insert((
+            Sprite::from_color(prop_color(prop.kind), prop.kind.size()),
+            Transform::from_translation(pos.0.extend(pos.0.y)),
+            RenderYtoZ,
+        ));
+    }
+}
+
+pub fn rendering_on_pickup_add<QF: QueryFilter>(
+    mut commands: Commands,
+    q_pickup: Query<(Entity, &Pickup, &Position), (Added<Pickup>, QF)>,
+) {
+    for (e, pickup, pos) in &q_pickup {
+        commands.entity(e).insert((
+            Sprite::from_color(pickup_color(pickup.kind), Vec2::splat(PICKUP_SIZE)),
+            Transform::from_translation(pos.0.extend(pos.0.y)),
+            RenderYtoZ,
+        ));
+    }
+}
@@ -16,11 +16,13 @@ impl Plugin for SharedUIPlugin {
                     ui_fade_system::<Sprite>,
                     ui_fade_system::<BackgroundColor>,
                     ui_fade_system::<ImageNode>,
+                    ui_fade_system::<TextColor>,
                 ),
             )
             .add_observer(trigger_ui_fade_set_color::<Sprite>)
             .add_observer(trigger_ui_fade_set_color::<BackgroundColor>)
-            .add_observer(trigger_ui_fade_set_color::<ImageNode>);
+            .add_observer(trigger_ui_fade_set_color::<ImageNode>)
+            .add_observer(trigger_ui_fade_set_color::<TextColor>);
     }
 }
 
@@ -67,6 +69,11 @@ impl CanFade for Sprite {
         self.color.set_alpha(percent)
     }
 }
+impl CanFade for TextColor {
+    fn set_alpha(&mut self, percent: f32) {
+        self.0.set_alpha(percent)
+    }
+}
 
 /// Should help to prevent with cases where something spawns in and is super visible immediately despite needing to fade in
 fn trigger_ui_fade_set_color<C: Component<Mutability = Mutable> + CanFade>(
@@ -2,9 +2,13 @@ use std::net::{Ipv4Addr, SocketAddr};
 
 use crate::{
     server::{
+        demo::DedicatedServerDemoPlugin,
+        drops::DedicatedServerDropsPlugin,
         enemies::{DedicatedServerEnemyPlugin, ServerEnemyRenderPlugin},
         game_rules::DedicatedServerGameRulesPlugin,
         lobby::DedicatedServerLobbyPlugin,
+        map::DedicatedServerMapPlugin,
+        match_clock::DedicatedServerMatchClockPlugin,
         players::ServerPlayerRenderPlugin,
         weapons::*,
     },
@@ -26,17 +30,23 @@ use lightyear::{
     },
 };
 use serde::{Deserialize, Serialize};
+pub mod demo;
+mod drops;
 mod enemies;
 mod game_rules;
 mod loading;
 mod lobby;
+mod map;
+mod match_clock;
 mod players;
 mod projectiles;
+mod props;
 mod weapons;
 
 use loading::DedicatedServerLoadingPlugin;
 use players::ServerPlayerPlugin;
 use projectiles::DedicatedServerProjectilePlugin;
+use props::DedicatedServerPropsPlugin;
 
 pub struct GameServerPlugin;
 impl Plugin for GameServerPlugin {
@@ -108,11 +118,16 @@ pub struct DedicatedServerPlugin;
 impl Plugin for DedicatedServerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
+            DedicatedServerDemoPlugin,
+            DedicatedServerDropsPlugin,
             DedicatedServerEnemyPlugin,
             DedicatedServerGameRulesPlugin,
             DedicatedServerLobbyPlugin,
             DedicatedServerLoadingPlugin,
+            DedicatedServerMapPlugin,
+            DedicatedServerMatchClockPlugin,
             DedicatedServerProjectilePlugin,
+            DedicatedServerPropsPlugin,
             DedicatedServerWeaponsPlugin,
         ))
         .add_systems(Startup, server_startup)
@@ -0,0 +1,228 @@
+use std::path::PathBuf;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::shared::{
+    combat::CombatSystemSet,
+    damage::Dead,
+    demo::*,
+    drops::{Pickup, PickupCollectedMessage, XPGem},
+    enemies::{Enemy, bosses::Boss, elites::Elite},
+    game_kinds::DefaultServerFilter,
+    game_object_spawning::despawn_game_objects,
+    game_rules::GameRules,
+    match_clock::MatchEndedMessage,
+    players::Player,
+    projectiles::Projectile,
+    props::Prop,
+    states::AppState,
+    stats::{components::Health, xp::LevelUpMessage},
+};
+
+/// Records a demo of every match, as long as there's a `DemoRecorder` to record to
+pub struct DedicatedServerDemoPlugin;
+
+impl Plugin for DedicatedServerDemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            start_demo.run_if(resource_exists::<DemoRecorder>),
+        )
+        .add_systems(
+            FixedUpdate,
+            record_demo_frame
+                .run_if(demo_recording)
+                .in_set(CombatSystemSet::PostCombatUpdate),
+        )
+        .add_systems(Update, record_demo_events.run_if(demo_recording))
+        .add_systems(
+            OnExit(AppState::InGame),
+            finish_demo
+                .run_if(demo_recording)
+                .before(despawn_game_objects),
+        )
+        .add_systems(
+            Last,
+            finish_demo.run_if(demo_recording.and(on_message::<AppExit>)),
+        )
+        .add_observer(record_player_died)
+        .add_observer(record_boss_spawned);
+    }
+}
+
+/// Where the server saves its demos. Each match starts a new demo, so only the last match the
+/// server played ends up in the file
+#[derive(Resource, Debug)]
+pub struct DemoRecorder {
+    pub path: PathBuf,
+    /// Seconds since the match started
+    pub elapsed: f32,
+    timer: Timer,
+    writer: Option<DemoWriter>,
+}
+
+impl DemoRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            elapsed: 0.0,
+            timer: Timer::from_seconds(DEMO_SNAPSHOT_INTERVAL, TimerMode::Repeating),
+            writer: None,
+        }
+    }
+
+    /// Gives up on the demo if the file can't be written to anymore, rather than failing on
+    /// every snapshot for the rest of the match
+    fn write(&mut self, f: impl FnOnce(&mut DemoWriter) -> std::io::Result<()>) {
+        if let Some(writer) = &mut self.writer
+            && let Err(e) = f(writer)
+        {
+            error!("Stopped recording the demo to {}: {e}", self.path.display());
+            self.writer = None;
+        }
+    }
+
+    fn push_event(&mut self, kind: DemoEventKind) {
+        let time = self.elapsed;
+        self.write(|writer| writer.write_event(DemoEvent { time, kind }));
+    }
+}
+
+fn demo_recording(recorder: Option<Res<DemoRecorder>>) -> bool {
+    recorder.is_some_and(|r| r.writer.is_some())
+}
+
+fn start_demo(mut recorder: ResMut<DemoRecorder>, rules: Res<GameRules>) {
+    recorder.elapsed = 0.0;
+    recorder.timer.reset();
+    match DemoWriter::create(&recorder.path, *rules) {
+        Ok(writer) => {
+            info!("Recording a demo to {}", recorder.path.display());
+            recorder.writer = Some(writer);
+        }
+        Err(e) => error!("Couldn't record a demo to {}: {e}", recorder.path.display()),
+    }
+}
+
+fn record_demo_frame(
+    time: Res<Time<Fixed>>,
+    mut recorder: ResMut<DemoRecorder>,
+    q_objects: Query<
+        (
+            Entity,
+            &Position,
+            Option<&Health>,
+            Has<Player>,
+            Option<&Enemy>,
+            Has<Elite>,
+            Has<Boss>,
+            Option<&Projectile>,
+            Has<XPGem>,
+            Option<&Pickup>,
+            Option<&Prop>,
+        ),
+        DefaultServerFilter,
+    >,
+) {
+    recorder.elapsed += time.delta_secs();
+    recorder.timer.tick(time.delta());
+    // The very first tick gets a snapshot too, so the demo starts with the match
+    let first = recorder.writer.as_ref().is_some_and(|w| w.frames == 0);
+    if !recorder.timer.just_finished() && !first {
+        return;
+    }
+
+    let entities = q_objects
+        .iter()
+        .filter_map(
+            |(ent, pos, m_health, player, m_enemy, elite, boss, m_proj, gem, m_pickup, m_prop)| {
+                let kind = if player {
+                    DemoEntityKind::Player
+                } else if let Some(enemy) = m_enemy {
+                    if boss {
+                        DemoEntityKind::Boss(enemy.kind)
+                    } else if elite {
+                        DemoEntityKind::Elite(enemy.kind)
+                    } else {
+                        DemoEntityKind::Enemy(enemy.kind)
+                    }
+                } else if let Some(proj) = m_proj {
+                    DemoEntityKind::Projectile(proj.faction)
+                } else if gem {
+                    DemoEntityKind::XPGem
+                } else if let Some(pickup) = m_pickup {
+                    DemoEntityKind::Pickup(pickup.kind)
+                } else if let Some(prop) = m_prop {
+                    DemoEntityKind::Prop(prop.kind)
+                } else {
+                    return None;
+                };
+                let health = m_health.map(|h| (h.current, h.max));
+                Some(DemoEntity::new(ent, kind, pos.0, health))
+            },
+        )
+        .collect();
+
+    let frame = DemoFrame {
+        time: recorder.elapsed,
+        entities,
+    };
+    recorder.write(|writer| writer.write_frame(&frame));
+}
+
+fn record_demo_events(
+    mut recorder: ResMut<DemoRecorder>,
+    mut level_ups: MessageReader<LevelUpMessage>,
+    mut pickups: MessageReader<PickupCollectedMessage>,
+    mut match_ended: MessageReader<MatchEndedMessage>,
+) {
+    for m in level_ups.read() {
+        recorder.push_event(DemoEventKind::LevelUp { level: m.level });
+    }
+    for m in pickups.read() {
+        recorder.push_event(DemoEventKind::PickupCollected { kind: m.kind });
+    }
+    for m in match_ended.read() {
+        recorder.push_event(DemoEventKind::MatchEnded { outcome: m.outcome });
+    }
+}
+
+fn record_player_died(
+    trigger: On<Add, Dead>,
+    m_recorder: Option<ResMut<DemoRecorder>>,
+    q_player: Query<(), (With<Player>, DefaultServerFilter)>,
+) {
+    if let Some(mut recorder) = m_recorder
+        && q_player.contains(trigger.entity)
+    {
+        recorder.push_event(DemoEventKind::PlayerDied {
+            player: trigger.entity.to_bits(),
+        });
+    }
+}
+
+fn record_boss_spawned(
+    trigger: On<Add, Boss>,
+    m_recorder: Option<ResMut<DemoRecorder>>,
+    q_enemy: Query<&Enemy>,
+) {
+    if let (Some(mut recorder), Ok(enemy)) = (m_recorder, q_enemy.get(trigger.entity)) {
+        recorder.push_event(DemoEventKind::BossSpawned { kind: enemy.kind });
+    }
+}
+
+/// Everything's already been written out by now, so this just closes the file
+fn finish_demo(mut recorder: ResMut<DemoRecorder>) {
+    let Some(mut writer) = recorder.writer.take() else {
+        return;
+    };
+    match writer.flush() {
+        Ok(()) => info!(
+            "Saved a demo of {} snapshots to {}",
+            writer.frames,
+            recorder.path.display()
+        ),
+        Err(e) => error!("Couldn't save the demo to {}: {e}", recorder.path.display()),
+    }
+}
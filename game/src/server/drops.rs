@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use crate::shared::{
+    combat::CombatSystemSet, drops::add_non_replicated_gem_components,
+    game_kinds::DefaultServerFilter, states::InGameState, stats::xp::add_xp,
+};
+
+pub struct DedicatedServerDropsPlugin;
+
+impl Plugin for DedicatedServerDropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            add_xp::<With<Replicate>>
+                .run_if(in_state(InGameState::InGame))
+                .in_set(CombatSystemSet::Combat),
+        )
+        .add_observer(add_non_replicated_gem_components::<DefaultServerFilter>);
+    }
+}
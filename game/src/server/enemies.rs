@@ -1,17 +1,20 @@
-use avian2d::prelude::*;
 use bevy::{prelude::*, time::common_conditions::on_timer};
 use lightyear::prelude::*;
-use rand::Rng;
 use std::time::Duration;
 
 use crate::{
     render::enemies::rendering_on_enemy_add,
     shared::{
-        colliders::CommonColliderBundle,
         combat::CombatSystemSet,
-        enemies::{spawner::*, *},
+        enemies::{
+            behaviours::enemy_behaviours, bosses::*, elites::*, lod::*,
+            navigation::update_flow_fields, ranged::enemy_ranged_attack, spawner::*, targeting::*,
+            *,
+        },
         game_kinds::{DefaultServerFilter, is_single_player},
-        states::InGameState,
+        pickups::hold_frozen_enemies,
+        spatial_index::rebuild_spatial_index,
+        states::{AppState, InGameState},
     },
 };
 
@@ -26,34 +29,42 @@ pub struct DedicatedServerEnemyPlugin;
 impl Plugin for DedicatedServerEnemyPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            OnEnter(InGameState::InGame),
-            spawn_enemy_spawn_manager.run_if(not(is_single_player)),
+            OnEnter(AppState::InGame),
+            (spawn_enemy_spawn_manager, spawn_boss_director).run_if(not(is_single_player)),
         )
         .add_systems(
             FixedUpdate,
             (
-                update_enemy_spawn_manager.run_if(resource_exists::<EnemySpawnManager>),
-                enemy_state_machine::<With<Replicate>, With<Replicate>>,
+                rebuild_spatial_index::<DefaultServerFilter>,
+                update_flow_fields::<With<Replicate>>,
+            )
+                .in_set(CombatSystemSet::PreCombat),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                update_enemy_spawn_manager::<With<Replicate>>
+                    .run_if(resource_exists::<EnemySpawnManager>),
+                update_boss_director::<With<Replicate>>.run_if(resource_exists::<BossDirector>),
+                (
+                    update_boss_phases::<With<Replicate>>,
+                    apply_boss_phase::<DefaultServerFilter>,
+                    enemy_state_machine::<With<Replicate>, With<Replicate>>,
+                    enemy_behaviours::<With<Replicate>, With<Replicate>>,
+                    hold_frozen_enemies::<With<Replicate>>,
+                    update_enemy_lod::<With<Replicate>, With<Replicate>>,
+                    integrate_simplified_enemies::<With<Replicate>>,
+                )
+                    .chain(),
+                enemy_ranged_attack::<With<Replicate>, With<Replicate>>,
+                (threat_from_damage, apply_threat::<With<Replicate>>).chain(),
+                elite_lifesteal::<With<Replicate>>,
             )
                 .run_if(in_state(InGameState::InGame))
                 .in_set(CombatSystemSet::Combat),
         )
-        .add_observer(add_non_replicated_enemy_components::<DefaultServerFilter>);
+        .add_observer(add_non_replicated_enemy_components::<DefaultServerFilter>)
+        .add_observer(on_enemy_death::<DefaultServerFilter>)
+        .add_observer(on_elite_death::<DefaultServerFilter>);
     }
 }
-
-/// TODO: Move this to the responsibility of a dedicated spawner that gets selectively spawned on client or server, depending on the context
-fn spawn_enemy(mut commands: Commands) {
-    let enemy = Enemy {
-        kind: EnemyKind::FacelessMan,
-        state: EnemyState::Spawning,
-    };
-    let mut rng = rand::rng();
-    let pos = (rng.random_range(-50.0..50.0), rng.random_range(-50.0..50.0));
-    commands.spawn((
-        CommonColliderBundle::from(enemy),
-        enemy,
-        Position(Vec2::new(pos.0, pos.1)),
-        EnemySpawnTimer::default(),
-    ));
-}
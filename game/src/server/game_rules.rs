@@ -8,6 +8,7 @@ impl Plugin for DedicatedServerGameRulesPlugin {
             (
                 receive_game_change_message::<Difficulty>,
                 receive_game_change_message::<MapKind>,
+                receive_game_change_message::<XPSharing>,
             )
                 .run_if(in_state(AppState::Lobby)),
         );
@@ -1,7 +1,9 @@
 use crate::shared::{
     game_kinds::{CurrentGameKind, MultiPlayerComponentOptions},
     game_object_spawning::spawn_game_object,
+    map::{CurrentMap, load_map},
     players::{CharacterKind, Player},
+    rng::{MatchRng, RngStream, RollbackRng, seed_match_rng},
     states::*,
     stats::{RawStatsList, xp::add_level_manager},
     weapons::{WeaponKind, add_weapon_to_player},
@@ -19,7 +21,14 @@ impl Plugin for DedicatedServerLoadingPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             OnEnter(AppState::LoadingLevel),
-            (add_level_manager, spawn_player_characters, tmp_move_to_game).chain(),
+            (
+                spawn_player_characters,
+                add_level_manager::<With<Replicate>>,
+                tmp_move_to_game,
+            )
+                .chain()
+                .after(load_map)
+                .after(seed_match_rng),
         );
     }
 }
@@ -27,11 +36,13 @@ impl Plugin for DedicatedServerLoadingPlugin {
 fn spawn_player_characters(
     mut commands: Commands,
     game_kinds: Res<CurrentGameKind>,
+    map: Res<CurrentMap>,
+    mut match_rng: ResMut<MatchRng>,
     q_clients: Query<(Entity, &RemoteId), With<LinkOf>>,
 ) {
+    let rng = match_rng.stream(RngStream::Players);
     for (ent, remote) in &q_clients {
-        let mut rng = rand::rng();
-        let pos = (rng.random_range(-50.0..50.0), rng.random_range(-50.0..50.0));
+        let pos = map.player_start.random_point(rng);
 
         let player = Player { client: remote.0 };
 
@@ -41,7 +52,8 @@ fn spawn_player_characters(
             MultiPlayerComponentOptions::from(player),
             (
                 player,
-                Position(Vec2::new(pos.0, pos.1)),
+                Position(pos),
+                RollbackRng::new(rng.random()),
                 ControlledBy {
                     owner: ent,
                     lifetime: Lifetime::default(),
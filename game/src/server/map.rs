@@ -0,0 +1,23 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use crate::shared::{
+    combat::CombatSystemSet,
+    map::{CurrentMap, clamp_to_map_bounds},
+    states::InGameState,
+};
+
+pub struct DedicatedServerMapPlugin;
+
+impl Plugin for DedicatedServerMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedPostUpdate,
+            clamp_to_map_bounds::<With<Replicate>>
+                .after(PhysicsSystems::Last)
+                .in_set(CombatSystemSet::PostPhysicsSet)
+                .run_if(in_state(InGameState::InGame).and(resource_exists::<CurrentMap>)),
+        );
+    }
+}
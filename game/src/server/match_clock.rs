@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use crate::shared::{
+    GameMainChannel,
+    combat::CombatSystemSet,
+    match_clock::*,
+    states::{AppState, InGameState},
+};
+
+pub struct DedicatedServerMatchClockPlugin;
+
+impl Plugin for DedicatedServerMatchClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), add_match_clock)
+            .add_systems(
+                FixedUpdate,
+                (
+                    tick_match_clock::<With<Replicate>>,
+                    check_match_end::<With<Replicate>, With<Replicate>>
+                        .pipe(server_send_match_ended),
+                )
+                    .chain()
+                    .in_set(CombatSystemSet::PostCombatUpdate)
+                    .run_if(in_state(InGameState::InGame)),
+            )
+            .add_systems(Update, move_to_post_game.run_if(in_state(AppState::InGame)));
+    }
+}
+
+/// Lets all of the clients know that the match is over, and then lets the server know too
+fn server_send_match_ended(
+    outcome: In<Option<MatchOutcome>>,
+    mut messages: MessageWriter<MatchEndedMessage>,
+    mut q_sender: Query<&mut MessageSender<MatchEndedMessage>>,
+) {
+    if let Some(outcome) = outcome.0 {
+        for mut sender in &mut q_sender {
+            sender.send::<GameMainChannel>(MatchEndedMessage { outcome });
+        }
+        messages.write(MatchEndedMessage { outcome });
+    }
+}
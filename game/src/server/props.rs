@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use crate::shared::{
+    combat::CombatSystemSet,
+    drops::add_non_replicated_pickup_components,
+    game_kinds::{DefaultServerFilter, is_single_player},
+    loot::open_chests,
+    pickups::vacuum_gems,
+    props::*,
+    rng::MatchRng,
+    states::{AppState, InGameState},
+};
+
+pub struct DedicatedServerPropsPlugin;
+
+impl Plugin for DedicatedServerPropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::InGame),
+            spawn_map_props.run_if(not(is_single_player)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                update_prop_spawner::<With<Replicate>>.run_if(resource_exists::<PropSpawner>),
+                vacuum_gems::<With<Replicate>>,
+                open_chests::<With<Replicate>>.run_if(resource_exists::<MatchRng>),
+            )
+                .run_if(in_state(InGameState::InGame))
+                .in_set(CombatSystemSet::Combat),
+        )
+        .add_observer(add_non_replicated_prop_components::<DefaultServerFilter>)
+        .add_observer(add_non_replicated_pickup_components::<DefaultServerFilter>)
+        .add_observer(on_prop_death::<DefaultServerFilter>);
+    }
+}
@@ -13,6 +13,7 @@ use lightyear::{
 pub mod colliders;
 pub mod combat;
 pub mod damage;
+pub mod demo;
 pub mod despawn_timer;
 pub mod drops;
 pub mod enemies;
@@ -21,8 +22,15 @@ pub mod game_object_spawning;
 pub mod game_rules;
 pub mod inputs;
 pub mod lobby;
+pub mod loot;
+pub mod map;
+pub mod match_clock;
+pub mod pickups;
 pub mod players;
 pub mod projectiles;
+pub mod props;
+pub mod rng;
+pub mod spatial_index;
 pub mod states;
 pub mod stats;
 pub mod weapons;
@@ -31,13 +39,22 @@ use colliders::*;
 use combat::CombatPlugin;
 use damage::SharedDamagePlugin;
 use despawn_timer::DespawnTimerPlugin;
-use enemies::EnemyProtocolPlugin;
+use drops::{DropsProtocolPlugin, SharedDropsPlugin};
+use enemies::{EnemyProtocolPlugin, SharedEnemyPlugin};
 use game_kinds::GameKindsPlugin;
+use game_object_spawning::despawn_game_objects;
 use game_rules::SharedGameRulesPlugin;
 use inputs::GameInputProtocolPlugin;
 use lobby::LobbyProtocolPlugin;
+use loot::SharedLootPlugin;
+use map::SharedMapPlugin;
+use match_clock::MatchClockProtocolPlugin;
+use pickups::{PickupsProtocolPlugin, SharedPickupsPlugin};
 use projectiles::ProjectileProtocolPlugin;
-use states::SharedStatesPlugin;
+use props::PropsProtocolPlugin;
+use rng::{RngProtocolPlugin, SharedRngPlugin};
+use spatial_index::SharedSpatialIndexPlugin;
+use states::{AppState, SharedStatesPlugin};
 use weapons::{SharedWeaponPlugin, WeaponProtocolPlugin};
 
 use crate::{
@@ -74,6 +91,13 @@ impl Plugin for GameSharedPlugin {
             GameKindsPlugin,
             SharedColliderPlugin,
             SharedDamagePlugin,
+            SharedDropsPlugin,
+            SharedEnemyPlugin,
+            SharedLootPlugin,
+            SharedMapPlugin,
+            SharedPickupsPlugin,
+            SharedRngPlugin,
+            SharedSpatialIndexPlugin,
             SharedStatesPlugin,
             SharedGameRulesPlugin,
             SharedWeaponPlugin,
@@ -96,7 +120,9 @@ impl Plugin for GameSharedPlugin {
                 .disable::<IslandPlugin>()
                 .disable::<IslandSleepingPlugin>(),
         ))
-        .insert_resource(Gravity::ZERO);
+        .insert_resource(Gravity::ZERO)
+        // Both a dedicated server and the client (which hosts single player) clean up after a match
+        .add_systems(OnExit(AppState::InGame), despawn_game_objects);
     }
 }
 
@@ -105,11 +131,16 @@ impl Plugin for GameProtocolPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             CollidersProtocolPlugin,
+            DropsProtocolPlugin,
             EnemyProtocolPlugin,
             LobbyProtocolPlugin,
+            MatchClockProtocolPlugin,
+            PickupsProtocolPlugin,
             PlayerProtocolPlugin,
             GameInputProtocolPlugin,
             ProjectileProtocolPlugin,
+            PropsProtocolPlugin,
+            RngProtocolPlugin,
             StatsProtocolPlugin,
             WeaponProtocolPlugin,
         ))
@@ -1,15 +1,25 @@
 use avian2d::prelude::*;
 use bevy::{platform::collections::HashMap, prelude::*};
 use lightyear::{prediction::SyncComponent, prelude::*};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::shared::{
-    combat::CombatSystemSet,
-    damage::{DamageBuffer, DamageInstance},
-    states::InGameState,
-    stats::components::Damage,
+use crate::{
+    shared::{
+        combat::CombatSystemSet,
+        damage::{DamageBuffer, DamageInstance, damage_chain},
+        pickups::Frozen,
+        rng::RollbackRng,
+        states::InGameState,
+        stats::components::{CritChance, CritDamage, Damage},
+    },
+    utils::CreatedBy,
 };
 
+/// How long (in seconds) something has to wait before it can apply collision damage
+/// to the same target again
+pub const COLLISION_DAMAGE_COOLDOWN: f32 = 2.0;
+
 pub struct SharedColliderPlugin;
 
 impl Plugin for SharedColliderPlugin {
@@ -90,6 +100,10 @@ pub enum ColliderTypes {
     //Can be picked up by pickup radius
     RemotePickup,
     PlayerRevive,
+    /// The static parts of the map that nothing gets to walk through
+    Obstacle,
+    /// Things in the world that players can break open
+    Prop,
 }
 
 /// There are many things that we may want to have happen upon collision betweeen two units:
@@ -141,31 +155,58 @@ impl CollisionEffect for ApplyDamage {
         coms.queue(move |world: &mut World| {
             // Get damage of the entity applying it
             let ent_dam = world.get::<Damage>(from);
-            let dam_val = if let Some(d) = ent_dam {
+            let mut dam_val = if let Some(d) = ent_dam {
                 d.0
             } else {
                 return;
             };
+            // Crits only happen for things that have both of the crit stats
+            let crit = match (
+                world.get::<CritChance>(from).copied(),
+                world.get::<CritDamage>(from).copied(),
+            ) {
+                (Some(cc), Some(cd)) if roll_for(world, from) < cc.0 => {
+                    dam_val *= cd.0;
+                    true
+                }
+                _ => false,
+            };
             let mut dam_buff = world.get_mut::<DamageBuffer>(to);
             if let Some(ref mut db) = dam_buff {
                 db.push(DamageInstance {
                     damage_source: from,
                     amount: dam_val,
+                    crit,
                 });
             }
         });
     }
 }
 
+/// A roll between 0 and 1 from the `RollbackRng` of whoever is behind `source`, so that a
+/// rolled back tick crits the same way the second time. Nobody behind it means no luck at all
+fn roll_for(world: &mut World, source: Entity) -> f32 {
+    let chain = damage_chain(
+        source,
+        |ent| world.get::<CreatedBy>(ent).map(|c| c.0),
+        |ent| world.get::<ChildOf>(ent).map(|p| p.parent()),
+    );
+    chain
+        .into_iter()
+        .find(|ent| world.get::<RollbackRng>(*ent).is_some())
+        .and_then(|ent| world.get_mut::<RollbackRng>(ent))
+        .map_or(1.0, |mut rng| rng.random::<f32>())
+}
+
 /// Applying damage is a more special case of collision based effects than I may have initially appreciated.
 /// In this system, we want to add entities to the list in the event that they collide with the entity, but are
 /// not in the list of entities that have been recently collided with.
 /// This can happen because of collision start, or as the result of an ongoing collision. So, we use the Collisions param
-/// rather than reading from the messages
+/// rather than reading from the messages. Anything that's frozen can't hurt what it's touching until it thaws
 fn collision_damage_system(
     collisions: Collisions,
     mut commands: Commands,
-    q_applies_damage: Query<&AppliesCollisionEffect<ApplyDamage>>,
+    q_applies_damage: Query<&AppliesCollisionEffect<ApplyDamage>, Without<Frozen>>,
     mut q_damage_target: Query<(Entity, &mut RecentlyCollided, &CollisionLayers)>,
 ) {
     for (ent_to_damage, mut recent_collided, layers) in &mut q_damage_target {
@@ -195,6 +236,33 @@ fn collision_damage_system(
     }
 }
 
+/// Unlike damage, things like pickups only need to happen once, when the two colliders first
+/// touch. So rather than tracking recent collisions, this reads the collision start messages
+pub fn collision_start_effect_system<E: CollisionEffect>(
+    mut commands: Commands,
+    mut collisions: MessageReader<CollisionStart>,
+    q_applies_effect: Query<&AppliesCollisionEffect<E>>,
+    q_layers: Query<&CollisionLayers>,
+) {
+    for contact in collisions.read() {
+        for (applying_entity, target) in [
+            (contact.collider1, contact.collider2),
+            (contact.collider2, contact.collider1),
+        ] {
+            let (Ok(applies_effect), Ok(layers)) =
+                (q_applies_effect.get(applying_entity), q_layers.get(target))
+            else {
+                continue;
+            };
+            if (layers.memberships.0 & applies_effect.to.0) != 0 {
+                applies_effect
+                    .eff
+                    .apply_to(&mut commands, target, applying_entity);
+            }
+        }
+    }
+}
+
 fn tick_rec_collided(
     time: Res<Time<Fixed>>,
     mut q_recents: Query<(Entity, &mut RecentlyCollided)>,
@@ -228,6 +296,9 @@ pub struct RecentlyCollided {
 pub struct CollisionDamageTimer(pub Timer);
 impl CollisionDamageTimer {
     pub fn new() -> Self {
-        Self(Timer::from_seconds(2.0, TimerMode::Once))
+        Self(Timer::from_seconds(
+            COLLISION_DAMAGE_COOLDOWN,
+            TimerMode::Once,
+        ))
     }
 }
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
+use lightyear::prelude::{Client, LocalTimeline, Tick};
 use serde::{Deserialize, Serialize};
 
-use crate::shared::{combat::CombatSystemSet, stats::components::Health};
+use crate::{
+    shared::{combat::CombatSystemSet, players::Player, stats::components::Health},
+    utils::CreatedBy,
+};
 
 #[derive(Component, Debug, Clone, Reflect, Deref, DerefMut, Default)]
 pub struct DamageBuffer(Vec<DamageInstance>);
@@ -10,28 +14,52 @@ pub struct DamageBuffer(Vec<DamageInstance>);
 pub struct DamageInstance {
     pub damage_source: Entity,
     pub amount: f32,
+    pub crit: bool,
 }
 
 #[derive(Component, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Reflect, Debug)]
 pub struct Dead;
 
+/// Whatever landed the killing blow. This goes on at the same time as `Dead`, so anything
+/// observing `Dead` being added can see it
+#[derive(Component, Clone, Copy, Reflect, Debug)]
+pub struct KilledBy(pub Entity);
+
 pub struct SharedDamagePlugin;
 
 impl Plugin for SharedDamagePlugin {
     fn build(&self, app: &mut App) {
-        app.add_message::<EntityKilledMessage>().add_systems(
-            FixedPostUpdate,
-            ((
-                apply_frame_damage,
-                clear_damage_buffer,
-                apply_dead_component,
-            )
-                .chain()
-                .in_set(CombatSystemSet::Cleanup),),
-        );
+        app.add_message::<EntityKilledMessage>()
+            .add_message::<AppliedDamageMessage>()
+            .add_systems(
+                FixedPostUpdate,
+                ((
+                    apply_frame_damage,
+                    clear_damage_buffer,
+                    apply_dead_component,
+                )
+                    .chain()
+                    .in_set(CombatSystemSet::Cleanup),),
+            );
     }
 }
 
+/// Written every time that damage actually lands on something, after the buffer is resolved.
+///
+/// This is intended for feedback (damage numbers, logs, etc.). It gets written wherever the damage
+/// is simulated, so on a client it can be written more than once for the same hit if a rollback
+/// replays the tick. Anything reading this on the client needs to keep that in mind
+#[derive(Message, Debug, Clone, Copy)]
+pub struct AppliedDamageMessage {
+    pub target: Entity,
+    pub source: Entity,
+    pub amount: f32,
+    pub crit: bool,
+    /// The client's tick that the damage landed on, which a rollback replays the hit on again.
+    /// A dedicated server has no client, so there's nothing here
+    pub tick: Option<Tick>,
+}
+
 #[derive(Message)]
 pub struct EntityKilledMessage {
     dead_entity: Entity,
@@ -40,9 +68,11 @@ pub struct EntityKilledMessage {
 
 fn apply_frame_damage(
     mut events: MessageWriter<EntityKilledMessage>,
-    //mut damage_events: MessageWriter<AppliedDamageLogMessage>,
+    mut damage_events: MessageWriter<AppliedDamageMessage>,
     mut q_health: Query<(Entity, &DamageBuffer, &mut Health), Without<Dead>>,
+    q_timeline: Query<&LocalTimeline, With<Client>>,
 ) {
+    let tick = q_timeline.iter().next().map(|timeline| timeline.tick());
     for (ent, buff, mut health) in &mut q_health {
         let mut health_to_set = health.current;
         let mut dead = false;
@@ -51,12 +81,13 @@ fn apply_frame_damage(
             .iter()
             .map(|dam| {
                 health_to_set -= dam.amount;
-                /*
-                damage_events.write(AppliedDamageLogMessage {
+                damage_events.write(AppliedDamageMessage {
+                    target: ent,
                     source: dam.damage_source,
                     amount: dam.amount,
+                    crit: dam.crit,
+                    tick,
                 });
-                */
                 if health_to_set <= 0.0 && !dead {
                     killed_by = Some(dam.damage_source);
                     dead = true;
@@ -89,7 +120,41 @@ fn apply_dead_component(
 ) {
     for e in events.read() {
         /*if q_check.get(e.dead_entity).is_err()  { */
-        commands.entity(e.dead_entity).insert(Dead);
+        commands
+            .entity(e.dead_entity)
+            .insert((Dead, KilledBy(e.responsible_entity)));
         //}
     }
 }
+
+/// Everything that could be behind some damage: the source itself, whatever created it (the
+/// weapon that fired a projectile), and whatever that belongs to (the player that has the
+/// weapon). When there's nothing further up, the last entity found just gets repeated.
+///
+/// This takes lookups rather than queries so that it works with a `World` just as well
+pub fn damage_chain(
+    source: Entity,
+    created_by: impl Fn(Entity) -> Option<Entity>,
+    parent_of: impl Fn(Entity) -> Option<Entity>,
+) -> [Entity; 3] {
+    let creator = created_by(source).unwrap_or(source);
+    let owner = parent_of(creator).unwrap_or(creator);
+    [source, creator, owner]
+}
+
+/// Works out which player (if any) is behind some damage, going from a projectile to the
+/// weapon that made it, and from the weapon to the player that has it
+pub fn responsible_player(
+    source: Entity,
+    q_created: &Query<&CreatedBy>,
+    q_parent: &Query<&ChildOf>,
+    q_player: &Query<(), With<Player>>,
+) -> Option<Entity> {
+    damage_chain(
+        source,
+        |ent| q_created.get(ent).ok().map(|c| c.0),
+        |ent| q_parent.get(ent).ok().map(|p| p.parent()),
+    )
+    .into_iter()
+    .find(|ent| q_player.contains(*ent))
+}
@@ -0,0 +1,285 @@
+//! Demos: what a dedicated server saw over the course of a match, for watching it back later.
+//!
+//! Unlike a replay, which plays the match out again from its inputs, a demo is just a series of
+//! snapshots of everything the server was replicating, taken every so often, along with the
+//! things that happened in between. Nothing gets simulated to watch one, so it still shows what
+//! actually happened when the simulation isn't deterministic (like when a client desynced).
+//!
+//! On disk, a demo is a header with the rules, followed by a stream of bincode records. Each
+//! snapshot only holds what changed since the one before it, and records get written as the
+//! match goes, so a server that goes down partway through still leaves everything up to that
+//! point behind. Loading one puts the full snapshots back together.
+//!
+//! The server records one with `server --demo <path>`, and `demo <path>` opens the viewer
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::{
+    drops::PickupKind, enemies::EnemyKind, game_rules::GameRules, match_clock::MatchOutcome,
+    projectiles::ProjectileFaction, props::PropKind,
+};
+
+/// Bumped whenever the records change in a way that old demos can't be read with
+pub const DEMO_FORMAT_VERSION: u32 = 1;
+/// How often (in seconds of match time) the server takes a snapshot
+pub const DEMO_SNAPSHOT_INTERVAL: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DemoEntityKind {
+    Player,
+    Enemy(EnemyKind),
+    Elite(EnemyKind),
+    Boss(EnemyKind),
+    Projectile(ProjectileFaction),
+    XPGem,
+    Pickup(PickupKind),
+    Prop(PropKind),
+}
+
+/// One thing in the world at the time of a snapshot. Positions are rounded to the nearest
+/// pixel, and health to the nearest percent, to keep the file small
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DemoEntity {
+    /// The server's entity, so the same thing can be followed from one snapshot to the next
+    pub id: u64,
+    pub kind: DemoEntityKind,
+    pub pos: IVec2,
+    /// Out of 100, for anything that has health
+    pub health: Option<u8>,
+}
+
+impl DemoEntity {
+    pub fn new(
+        entity: Entity,
+        kind: DemoEntityKind,
+        pos: Vec2,
+        health: Option<(f32, f32)>,
+    ) -> Self {
+        Self {
+            id: entity.to_bits(),
+            kind,
+            pos: pos.round().as_ivec2(),
+            health: health.map(|(current, max)| {
+                (current / max.max(f32::EPSILON) * 100.0).clamp(0.0, 100.0) as u8
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DemoFrame {
+    /// Seconds since the match started
+    pub time: f32,
+    pub entities: Vec<DemoEntity>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DemoEventKind {
+    PlayerDied { player: u64 },
+    BossSpawned { kind: EnemyKind },
+    LevelUp { level: u8 },
+    PickupCollected { kind: PickupKind },
+    MatchEnded { outcome: MatchOutcome },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DemoEvent {
+    pub time: f32,
+    pub kind: DemoEventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoFile {
+    pub rules: GameRules,
+    /// These go from the start of the match to the end
+    pub frames: Vec<DemoFrame>,
+    pub events: Vec<DemoEvent>,
+}
+
+impl DemoFile {
+    pub fn new(rules: GameRules) -> Self {
+        Self {
+            rules,
+            frames: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Reads everything that made it into the file. A demo that got cut off (because the
+    /// server went down while recording it) loads up to the last record that was written out
+    pub fn load(path: &Path) -> Self {
+        let file =
+            File::open(path).unwrap_or_else(|_| panic!("Failed to read file {:?}", path.display()));
+        let mut reader = BufReader::new(file);
+        let Ok(DemoRecord::Header { version, rules }) = decode_record(&mut reader) else {
+            panic!("{:?} isn't a demo", path.display());
+        };
+        if version != DEMO_FORMAT_VERSION {
+            panic!(
+                "{:?} is a version {version} demo, but only version {DEMO_FORMAT_VERSION} can be read",
+                path.display()
+            );
+        }
+
+        let mut demo = Self::new(rules);
+        let mut entities: HashMap<u64, DemoEntity> = HashMap::default();
+        // Stopping right between two records is the end of the file, anywhere else means
+        // that the last one didn't get written out in full
+        while reader.fill_buf().is_ok_and(|buf| !buf.is_empty()) {
+            match decode_record(&mut reader) {
+                Ok(DemoRecord::Frame(delta)) => {
+                    for id in &delta.removed {
+                        entities.remove(id);
+                    }
+                    entities.extend(delta.changed.iter().map(|e| (e.id, *e)));
+                    let mut frame_entities: Vec<DemoEntity> = entities.values().copied().collect();
+                    frame_entities.sort_by_key(|e| e.id);
+                    demo.frames.push(DemoFrame {
+                        time: delta.time,
+                        entities: frame_entities,
+                    });
+                }
+                Ok(DemoRecord::Event(event)) => demo.events.push(event),
+                Ok(DemoRecord::Header { .. }) => warn!("Skipping a second header in the demo"),
+                Err(e) => {
+                    warn!(
+                        "The demo ends early, after {} snapshots: {e}",
+                        demo.frames.len()
+                    );
+                    break;
+                }
+            }
+        }
+        demo
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut writer = DemoWriter::create(path, self.rules)?;
+        for frame in &self.frames {
+            writer.write_frame(frame)?;
+        }
+        for event in &self.events {
+            writer.write_event(*event)?;
+        }
+        writer.flush()
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |f| f.time)
+    }
+
+    /// The index of the last snapshot taken at or before `time`
+    pub fn frame_at(&self, time: f32) -> usize {
+        self.frames
+            .partition_point(|f| f.time <= time)
+            .saturating_sub(1)
+    }
+
+    /// Everything that happened in the `window` seconds leading up to `time`
+    pub fn events_before(&self, time: f32, window: f32) -> impl Iterator<Item = &DemoEvent> {
+        self.events
+            .iter()
+            .filter(move |e| e.time <= time && e.time > time - window)
+    }
+}
+
+/// Only what changed about the world since the snapshot before this one
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DemoFrameDelta {
+    pub time: f32,
+    /// Anything that's new, or that moved or changed
+    pub changed: Vec<DemoEntity>,
+    /// The ids of everything that was in the last snapshot but isn't anymore
+    pub removed: Vec<u64>,
+}
+
+impl DemoFrameDelta {
+    /// What it takes to get from `last` to `frame`
+    pub fn between(last: &HashMap<u64, DemoEntity>, frame: &DemoFrame) -> Self {
+        let changed = frame
+            .entities
+            .iter()
+            .filter(|e| last.get(&e.id) != Some(*e))
+            .copied()
+            .collect();
+        let ids: HashSet<u64> = frame.entities.iter().map(|e| e.id).collect();
+        let removed = last
+            .keys()
+            .filter(|id| !ids.contains(*id))
+            .copied()
+            .collect();
+        Self {
+            time: frame.time,
+            changed,
+            removed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum DemoRecord {
+    Header { version: u32, rules: GameRules },
+    Frame(DemoFrameDelta),
+    Event(DemoEvent),
+}
+
+fn decode_record(reader: &mut BufReader<File>) -> Result<DemoRecord, bincode::error::DecodeError> {
+    bincode::serde::decode_from_std_read(reader, bincode::config::standard())
+}
+
+/// Writes a demo out as it gets recorded. Every record gets flushed as soon as it's written, so
+/// that the file has everything up until whatever stopped the recording
+#[derive(Debug)]
+pub struct DemoWriter {
+    file: BufWriter<File>,
+    /// Everything in the last snapshot, for working out the next delta
+    last: HashMap<u64, DemoEntity>,
+    pub frames: usize,
+}
+
+impl DemoWriter {
+    pub fn create(path: &Path, rules: GameRules) -> std::io::Result<Self> {
+        let mut writer = Self {
+            file: BufWriter::new(File::create(path)?),
+            last: HashMap::default(),
+            frames: 0,
+        };
+        writer.write_record(&DemoRecord::Header {
+            version: DEMO_FORMAT_VERSION,
+            rules,
+        })?;
+        Ok(writer)
+    }
+
+    fn write_record(&mut self, record: &DemoRecord) -> std::io::Result<()> {
+        bincode::serde::encode_into_std_write(record, &mut self.file, bincode::config::standard())
+            .map_err(std::io::Error::other)?;
+        Ok(())
+    }
+
+    pub fn write_frame(&mut self, frame: &DemoFrame) -> std::io::Result<()> {
+        let delta = DemoFrameDelta::between(&self.last, frame);
+        self.last = frame.entities.iter().map(|e| (e.id, *e)).collect();
+        self.frames += 1;
+        self.write_record(&DemoRecord::Frame(delta))?;
+        self.flush()
+    }
+
+    pub fn write_event(&mut self, event: DemoEvent) -> std::io::Result<()> {
+        self.write_record(&DemoRecord::Event(event))?;
+        self.flush()
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
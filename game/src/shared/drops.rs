@@ -1,4 +1,214 @@
-use bevy::prelude::*;
+//! The things that get left behind for players to pick up, like XP gems
+use avian2d::prelude::*;
+use bevy::{ecs::query::QueryFilter, prelude::*};
+use lightyear::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::shared::{
+    colliders::*,
+    combat::CombatSystemSet,
+    game_kinds::{GameKinds, MultiPlayerComponentOptions},
+    game_object_spawning::spawn_game_object,
+    pickups::insert_pickup_effect,
+    states::InGameState,
+    stats::xp::ApplyXPMessage,
+};
+
+/// How much XP something leaves behind when it dies
 #[derive(Component)]
 pub struct XPDrop(pub f32);
+
+pub struct DropsProtocolPlugin;
+
+impl Plugin for DropsProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_component::<XPGem>().add_prediction();
+        app.register_component::<Pickup>().add_prediction();
+    }
+}
+
+pub struct SharedDropsPlugin;
+
+impl Plugin for SharedDropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<PickupCollectedMessage>().add_systems(
+            FixedPostUpdate,
+            collision_start_effect_system::<CollectXP>
+                .after(PhysicsSystems::Last)
+                .in_set(CombatSystemSet::PostPhysicsSet)
+                .run_if(in_state(InGameState::InGame)),
+        );
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct XPGem {
+    pub amount: f32,
+}
+
+impl From<XPGem> for CommonColliderBundle {
+    fn from(_value: XPGem) -> Self {
+        Self::new(
+            RigidBody::Static,
+            Collider::circle(8.0),
+            1.0,
+            [ColliderTypes::RemotePickup].into(),
+            [ColliderTypes::Player, ColliderTypes::PlayerPickupRadius].into(),
+        )
+    }
+}
+
+impl From<XPGem> for MultiPlayerComponentOptions {
+    fn from(_value: XPGem) -> Self {
+        Self {
+            pred: true,
+            interp: false,
+        }
+    }
+}
+
+/// Gives the XP in a gem to whoever touches it, and gets rid of the gem.
+///
+/// `to` is the player, and `from` is the gem
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct CollectXP;
+
+impl CollisionEffect for CollectXP {
+    fn apply_to(&self, coms: &mut Commands, to: Entity, from: Entity) {
+        coms.queue(move |world: &mut World| {
+            // Two players can touch the same gem on the same tick, or a vacuumed gem can
+            // reach its player as it collides with them, so whoever gets here first takes it
+            let Some(gem) = world.get::<XPGem>(from).copied() else {
+                return;
+            };
+            world.despawn(from);
+            world.write_message(ApplyXPMessage {
+                collector: to,
+                amount: gem.amount,
+            });
+        });
+    }
+}
+
+/// The gem is only collectable where the game is actually being simulated, so the collection
+/// effect is added here rather than being replicated
+pub fn spawn_xp_gem(
+    commands: &mut Commands,
+    game_kind: GameKinds,
+    pos: Vec2,
+    amount: f32,
+) -> Entity {
+    let gem = XPGem { amount };
+    spawn_game_object(
+        commands,
+        game_kind,
+        MultiPlayerComponentOptions::from(gem),
+        (
+            gem,
+            Position(pos),
+            AppliesCollisionEffect::new([ColliderTypes::Player].into(), CollectXP),
+        ),
+    )
+}
+
+pub fn add_non_replicated_gem_components<QF: QueryFilter>(
+    trigger: On<Add, XPGem>,
+    mut commands: Commands,
+    q_gem: Query<&XPGem, QF>,
+) {
+    if let Ok(gem) = q_gem.get(trigger.entity) {
+        commands.entity(trigger.entity).insert((
+            Name::from("XP Gem"),
+            CommonColliderBundle::from(*gem),
+            Sensor,
+        ));
+    }
+}
+
+/// Everything that can be dropped for a player to walk over, other than XP
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum PickupKind {
+    Heal {
+        amount: f32,
+    },
+    Magnet,
+    Bomb {
+        damage: f32,
+    },
+    Freeze {
+        duration: f32,
+    },
+    Gold {
+        amount: u32,
+    },
+    /// Opens up into a few more pickups, rolled from the chest loot table
+    Chest,
+}
+
+/// Unlike gems, these don't get pulled in by the pickup radius. The player has to actually
+/// walk over them
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Pickup {
+    pub kind: PickupKind,
+}
+
+impl From<Pickup> for CommonColliderBundle {
+    fn from(_value: Pickup) -> Self {
+        Self::new(
+            RigidBody::Static,
+            Collider::circle(12.0),
+            1.0,
+            [ColliderTypes::StaticPickup].into(),
+            [ColliderTypes::Player].into(),
+        )
+    }
+}
+
+impl From<Pickup> for MultiPlayerComponentOptions {
+    fn from(_value: Pickup) -> Self {
+        Self {
+            pred: true,
+            interp: false,
+        }
+    }
+}
+
+/// Written wherever the game is simulated, once a player has taken a pickup
+#[derive(Message, Debug, Clone, Copy)]
+pub struct PickupCollectedMessage {
+    pub collector: Entity,
+    pub kind: PickupKind,
+}
+
+/// Like gems, only the simulating side gets the pickup's effect, so a client can never
+/// decide that it picked something up
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    game_kind: GameKinds,
+    pos: Vec2,
+    kind: PickupKind,
+) -> Entity {
+    let pickup = Pickup { kind };
+    let ent = spawn_game_object(
+        commands,
+        game_kind,
+        MultiPlayerComponentOptions::from(pickup),
+        (pickup, Position(pos)),
+    );
+    insert_pickup_effect(&mut commands.entity(ent), kind);
+    ent
+}
+
+pub fn add_non_replicated_pickup_components<QF: QueryFilter>(
+    trigger: On<Add, Pickup>,
+    mut commands: Commands,
+    q_pickup: Query<&Pickup, QF>,
+) {
+    if let Ok(pickup) = q_pickup.get(trigger.entity) {
+        commands.entity(trigger.entity).insert((
+            Name::from("Pickup"),
+            CommonColliderBundle::from(*pickup),
+            Sensor,
+        ));
+    }
+}
@@ -1,18 +1,46 @@
 use avian2d::prelude::*;
 use bevy::{ecs::query::QueryFilter, prelude::*};
 use lightyear::prelude::*;
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     shared::{
-        colliders::*, damage::Dead, game_kinds::*, game_object_spawning::*, players::Player,
-        stats::RawStatsList,
+        colliders::*,
+        damage::{Dead, KilledBy, responsible_player},
+        drops::{PickupKind, XPDrop, spawn_pickup, spawn_xp_gem},
+        game_kinds::*,
+        game_object_spawning::*,
+        game_rules::DifficultyModifiers,
+        loot::{LootTables, drop_loot, luck_of},
+        pickups::Frozen,
+        players::{Player, Threat},
+        rng::{MatchRng, RngStream},
+        spatial_index::SpatialIndex,
+        stats::{
+            RawStatsList,
+            components::{Luck, MovementSpeed},
+        },
     },
-    utils::AssetFolder,
+    utils::{AssetFolder, CreatedBy},
 };
 
+pub mod behaviours;
+pub mod bosses;
+pub mod definitions;
+pub mod elites;
+pub mod lod;
+pub mod navigation;
+pub mod ranged;
 pub mod spawner;
+pub mod targeting;
+
+use behaviours::{EnemyBehaviourState, spawn_splitter_children};
+use bosses::Boss;
+use definitions::{EnemyBehaviour, EnemyRegistry, load_enemy_registry};
+use elites::{Elite, load_elite_table};
+use navigation::{FlowFields, SEPARATION_WEIGHT, enemy_separation};
+use ranged::RangedAttack;
+use targeting::{RaiseThreatMessage, RetargetTimer, TargetablePlayer, pick_target, target_score};
 
 #[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
 pub struct Enemy {
@@ -20,23 +48,6 @@ pub struct Enemy {
     pub state: EnemyState,
 }
 
-impl From<Enemy> for CommonColliderBundle {
-    fn from(value: Enemy) -> Self {
-        Self::new(
-            RigidBody::Dynamic,
-            Collider::capsule(20.0, 30.0),
-            1.0,
-            [ColliderTypes::Enemy].into(),
-            [
-                ColliderTypes::Enemy,
-                ColliderTypes::Player,
-                ColliderTypes::PlayerProjectile,
-            ]
-            .into(),
-        )
-    }
-}
-
 impl From<Enemy> for MultiPlayerComponentOptions {
     fn from(value: Enemy) -> Self {
         Self {
@@ -46,21 +57,24 @@ impl From<Enemy> for MultiPlayerComponentOptions {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect, Default)]
+/// The id that an enemy's definition declares, for looking it up in the `EnemyRegistry`.
+/// Everything else about the enemy lives in its definition
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Reflect,
+    Default,
+)]
 #[reflect(Default)]
-pub enum EnemyKind {
-    #[default]
-    FacelessMan,
-}
-
-impl From<EnemyKind> for AssetFolder {
-    fn from(value: EnemyKind) -> Self {
-        let string = match value {
-            EnemyKind::FacelessMan => "enemies/faceless".into(),
-        };
-        Self(string)
-    }
-}
+pub struct EnemyKind(pub u16);
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum EnemyState {
@@ -83,48 +97,92 @@ pub struct EnemyProtocolPlugin;
 impl Plugin for EnemyProtocolPlugin {
     fn build(&self, app: &mut App) {
         app.register_component::<Enemy>();
+        app.register_component::<EnemyBehaviourState>()
+            .add_prediction();
+        app.register_component::<RetargetTimer>().add_prediction();
+        app.register_component::<Boss>().add_prediction();
+        app.register_component::<Elite>().add_prediction();
+    }
+}
+
+pub struct SharedEnemyPlugin;
+
+impl Plugin for SharedEnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<RaiseThreatMessage>()
+            .init_resource::<FlowFields>()
+            .add_systems(Startup, (load_enemy_registry, load_elite_table));
     }
 }
 
-pub fn spawn_enemy(commands: &mut Commands, e_kind: EnemyKind, game_kind: GameKinds) {
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    registry: &EnemyRegistry,
+    e_kind: EnemyKind,
+    game_kind: GameKinds,
+    pos: Vec2,
+    difficulty: &DifficultyModifiers,
+    elite: Option<Elite>,
+) -> Entity {
+    let def = registry.get(e_kind);
+    let elite_stats = elite.as_ref().map(|e| e.stats).unwrap_or_default();
     let enemy = Enemy {
         kind: e_kind,
         state: EnemyState::Spawning,
     };
-    let mut rng = rand::rng();
-    let pos = (rng.random_range(-50.0..50.0), rng.random_range(-50.0..50.0));
     let e_ent = spawn_game_object(
         commands,
         game_kind,
         MultiPlayerComponentOptions::from(enemy),
         (
             enemy,
-            Position(Vec2::new(pos.0, pos.1)),
+            Position(pos),
             EnemySpawnTimer::default(),
+            RetargetTimer::default(),
             AppliesCollisionEffect::new([ColliderTypes::Player].into(), ApplyDamage),
+            XPDrop(def.xp_drop * difficulty.xp_yield * elite_stats.xp),
         ),
     );
 
-    let stats = RawStatsList::import_stats(e_kind);
+    let mut stats = def.stats.clone();
+    difficulty.apply_to_enemy_stats(&mut stats);
+    elite_stats.apply_to_enemy_stats(&mut stats);
     stats.apply_to_character(e_ent, commands);
+    // The behaviour state is predicted, so it has to come from the server along with the enemy
+    if let Some(state) = def.behaviour.initial_state() {
+        commands.entity(e_ent).insert(state);
+    }
+    if def.boss.is_some() {
+        commands.entity(e_ent).insert(Boss::default());
+    }
+    if let Some(elite) = elite {
+        commands.entity(e_ent).insert(elite);
+    }
+    e_ent
 }
 
+/// Runs in `FixedUpdate`, so the timers count ticks rather than however long the frame was
 pub fn enemy_state_machine<EnemyQF: QueryFilter, PlayerQF: QueryFilter>(
     mut commands: Commands,
-    time: Res<Time<Virtual>>,
+    time: Res<Time>,
+    flow: Res<FlowFields>,
+    index: Res<SpatialIndex>,
     mut q_enemy: Query<
         (
             Entity,
             &mut Enemy,
             &Position,
             &mut LinearVelocity,
+            &MovementSpeed,
             Option<&mut EnemySpawnTimer>,
+            Option<&RangedAttack>,
+            Option<&mut RetargetTimer>,
         ),
         (EnemyQF),
     >,
-    q_targets: Query<(Entity, &Position), (With<Player>, Without<Enemy>, PlayerQF)>,
+    q_targets: Query<(Entity, &Position, &Threat), (TargetablePlayer, PlayerQF)>,
 ) {
-    for (ent, mut enemy, e_pos, mut e_lv, mut m_timer) in &mut q_enemy {
+    for (ent, mut enemy, e_pos, mut e_lv, e_ms, mut m_timer, m_ranged, m_retarget) in &mut q_enemy {
         match enemy.state {
             EnemyState::Spawning => {
                 let timer = if m_timer.is_none() {
@@ -139,26 +197,46 @@ pub fn enemy_state_machine<EnemyQF: QueryFilter, PlayerQF: QueryFilter>(
                 }
             }
             EnemyState::LookForTargets => {
-                let closest = q_targets
-                    .iter()
-                    .min_by(|p1, p2| {
-                        let d1 = p1.1.distance(e_pos.0);
-                        let d2 = p2.1.distance(e_pos.0);
-                        d1.total_cmp(&d2)
-                    })
-                    .map(|player| player.0);
-                if let Some(p_ent) = closest {
+                let candidates = q_targets.iter().map(|(p_ent, p_pos, threat)| {
+                    (p_ent, target_score(e_pos.0, p_pos.0, threat.0))
+                });
+                if let Some(p_ent) = pick_target(None, candidates) {
                     enemy.state = EnemyState::MovingTo(p_ent)
                 }
             }
             EnemyState::MovingTo(player) => {
-                let e_ms = 30.0;
-                if let Ok((_, p_pos)) = q_targets.get(player) {
-                    let dir = (p_pos.0 - e_pos.0).normalize_or_zero();
-                    e_lv.0 = dir * e_ms;
-                } else {
-                    enemy.state = EnemyState::LookForTargets
+                let Ok((_, p_pos, p_threat)) = q_targets.get(player) else {
+                    enemy.state = EnemyState::LookForTargets;
+                    continue;
+                };
+                let (mut t_ent, mut t_pos) = (player, p_pos.0);
+                if let Some(mut retarget) = m_retarget {
+                    retarget.0 -= time.delta_secs();
+                    if retarget.0 <= 0.0 {
+                        *retarget = RetargetTimer::default();
+                        let current = (player, target_score(e_pos.0, p_pos.0, p_threat.0));
+                        let candidates = q_targets.iter().map(|(p_ent, p_pos, threat)| {
+                            (p_ent, target_score(e_pos.0, p_pos.0, threat.0))
+                        });
+                        if let Some(new_target) = pick_target(Some(current), candidates)
+                            && let Ok((_, new_pos, _)) = q_targets.get(new_target)
+                        {
+                            enemy.state = EnemyState::MovingTo(new_target);
+                            (t_ent, t_pos) = (new_target, new_pos.0);
+                        }
+                    }
                 }
+                let to_target = t_pos - e_pos.0;
+                let direct = to_target.normalize_or_zero();
+                let approach = m_ranged.map_or(1.0, |r| r.approach(to_target.length()));
+                // Backing off doesn't need to path anywhere
+                let heading = if approach > 0.0 {
+                    flow.direction(t_ent, e_pos.0).unwrap_or(direct) * approach
+                } else {
+                    direct * approach
+                };
+                let separation = enemy_separation(&index, ent, e_pos.0) * SEPARATION_WEIGHT;
+                e_lv.0 = (heading + separation).clamp_length_max(1.0) * e_ms.current;
             }
             EnemyState::Dying => {}
         }
@@ -171,29 +249,90 @@ pub fn enemy_state_machine<EnemyQF: QueryFilter, PlayerQF: QueryFilter>(
 pub fn add_non_replicated_enemy_components<QF: QueryFilter>(
     trigger: On<Add, Enemy>,
     mut commands: Commands,
+    registry: Res<EnemyRegistry>,
     q_to_attach: Query<&Enemy, (QF)>,
 ) {
     if let Ok(en) = q_to_attach.get(trigger.entity) {
+        let def = registry.get(en.kind);
         commands.entity(trigger.entity).insert((
-            Name::from("Enemy"),
+            Name::from(def.name.clone()),
             EnemySpawnTimer::default(),
-            CommonColliderBundle::from(*en),
+            def.collider_bundle(),
             RecentlyCollided::default(),
+            def.behaviour.clone(),
         ));
+        // Needed on the client as well, so that the predicted enemy keeps its distance too
+        if let Some(ranged) = def.behaviour.ranged_attack() {
+            commands.entity(trigger.entity).insert(ranged);
+        }
     }
 }
 
-/*
-pub fn on_enemy_death(
+/// Dead enemies leave their XP behind as a gem, along with whatever the loot tables come up
+/// with for whoever killed them (splitters leave their children, and bosses their reward and
+/// a chest too), and get cleaned up
+pub fn on_enemy_death<QF: QueryFilter>(
     trigger: On<Add, Dead>,
     mut commands: Commands,
-    q_enemy: Query<&Position, With<Enemy>>
+    gk: Res<CurrentGameKind>,
+    registry: Res<EnemyRegistry>,
+    difficulty: Res<DifficultyModifiers>,
+    tables: Res<LootTables>,
+    mut rng: ResMut<MatchRng>,
+    q_enemy: Query<
+        (
+            &Enemy,
+            &Position,
+            Option<&XPDrop>,
+            &EnemyBehaviour,
+            Has<Elite>,
+            Option<&KilledBy>,
+        ),
+        QF,
+    >,
+    q_created: Query<&CreatedBy>,
+    q_parent: Query<&ChildOf>,
+    q_player: Query<(), With<Player>>,
+    q_luck: Query<&Luck, With<Player>>,
 ) {
-    if let Ok(pos) = q_enemy.get(trigger.entity) {\
-        commands.spawn((
-            pos.clone(),
-
-        ))
+    if let Ok((enemy, pos, m_drop, behaviour, is_elite, m_killer)) = q_enemy.get(trigger.entity) {
+        if let Some(drop) = m_drop {
+            spawn_xp_gem(&mut commands, gk.0.unwrap(), pos.0, drop.0);
+        }
+        let killer =
+            m_killer.and_then(|k| responsible_player(k.0, &q_created, &q_parent, &q_player));
+        let table = if is_elite {
+            &tables.elite
+        } else {
+            &tables.enemy
+        };
+        drop_loot(
+            &mut commands,
+            &gk,
+            table,
+            luck_of(killer, &q_luck),
+            rng.stream(RngStream::Loot),
+            pos.0,
+        );
+        if let Some(boss) = &registry.get(enemy.kind).boss {
+            spawn_xp_gem(
+                &mut commands,
+                gk.0.unwrap(),
+                pos.0,
+                boss.reward_xp * difficulty.xp_yield,
+            );
+            spawn_pickup(&mut commands, gk.0.unwrap(), pos.0, PickupKind::Chest);
+        }
+        if let EnemyBehaviour::Splitter(splitter) = behaviour {
+            spawn_splitter_children(
+                &mut commands,
+                &registry,
+                gk.0.unwrap(),
+                splitter,
+                pos.0,
+                &difficulty,
+            );
+        }
+        commands.entity(trigger.entity).despawn();
     }
 }
- */
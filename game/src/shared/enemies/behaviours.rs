@@ -0,0 +1,228 @@
+//! The behaviour layer that sits on top of `enemy_state_machine`.
+//!
+//! The state machine decides who an enemy is going after, and gives it a plain chase velocity.
+//! Anything with a more interesting archetype then gets its velocity overridden here, based on
+//! its `EnemyBehaviourState`. That state is predicted, and it only ever moves forward on the fixed
+//! timestep without any randomness, so replaying a tick after a rollback lands in the same place
+use super::*;
+use crate::shared::enemies::definitions::EnemyBehaviour;
+
+/// How hard a swarmer pulls itself back onto its orbit
+const ORBIT_CORRECTION: f32 = 5.0;
+
+/// Winds up in place, then dashes in a straight line at wherever the target was
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct ChargerBehaviour {
+    /// How close the target has to be before the charger starts winding up
+    pub trigger_range: f32,
+    /// Seconds spent standing still before the dash, so players can see it coming
+    pub windup: f32,
+    /// Seconds that the dash lasts
+    pub dash_time: f32,
+    /// Multiplies the movement speed while dashing
+    pub dash_speed: f32,
+    /// Seconds spent catching its breath after a dash
+    pub recover: f32,
+}
+
+/// Splits into smaller enemies when it dies
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct SplitterBehaviour {
+    /// The folder of the enemy that gets spawned, like "faceless"
+    pub child: String,
+    pub count: u8,
+    /// How far from the splitter the children show up
+    pub spread: f32,
+}
+
+/// Circles its target for a while before diving straight in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct SwarmerBehaviour {
+    pub orbit_radius: f32,
+    /// Radians per second around the target
+    pub orbit_speed: f32,
+    /// Seconds spent circling before each dive
+    pub orbit_time: f32,
+    /// Seconds that the dive lasts
+    pub dive_time: f32,
+    /// Multiplies the movement speed while diving
+    pub dive_speed: f32,
+}
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct EnemyBehaviourState {
+    pub phase: BehaviourPhase,
+    /// Seconds spent in the current phase
+    pub timer: f32,
+}
+
+impl Default for EnemyBehaviourState {
+    fn default() -> Self {
+        Self {
+            phase: BehaviourPhase::Approach,
+            timer: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum BehaviourPhase {
+    /// Closing the distance like any other enemy
+    Approach,
+    WindUp,
+    /// Locked into a direction until the dash is over
+    Dash(Vec2),
+    Recover,
+    /// The current angle around the target
+    Orbit(f32),
+    Dive(Vec2),
+}
+
+impl EnemyBehaviourState {
+    fn enter(&mut self, phase: BehaviourPhase) {
+        self.phase = phase;
+        self.timer = 0.0;
+    }
+
+    /// Moves the behaviour forward by one tick, and returns the velocity that the enemy should
+    /// have, or `None` if the plain chase from the state machine should be left alone
+    pub fn step(
+        &mut self,
+        behaviour: &EnemyBehaviour,
+        e_pos: Vec2,
+        t_pos: Vec2,
+        ms: f32,
+        dt: f32,
+    ) -> Option<Vec2> {
+        self.timer += dt;
+        let to_target = t_pos - e_pos;
+        let dir = to_target.normalize_or_zero();
+        let velo = match behaviour {
+            EnemyBehaviour::Charger(charger) => match self.phase {
+                BehaviourPhase::Approach => {
+                    if to_target.length() <= charger.trigger_range {
+                        self.enter(BehaviourPhase::WindUp);
+                        Vec2::ZERO
+                    } else {
+                        dir * ms
+                    }
+                }
+                BehaviourPhase::WindUp => {
+                    if self.timer >= charger.windup {
+                        self.enter(BehaviourPhase::Dash(dir));
+                        dir * ms * charger.dash_speed
+                    } else {
+                        Vec2::ZERO
+                    }
+                }
+                BehaviourPhase::Dash(dash_dir) => {
+                    if self.timer >= charger.dash_time {
+                        self.enter(BehaviourPhase::Recover);
+                        Vec2::ZERO
+                    } else {
+                        dash_dir * ms * charger.dash_speed
+                    }
+                }
+                BehaviourPhase::Recover => {
+                    if self.timer >= charger.recover {
+                        self.enter(BehaviourPhase::Approach);
+                        dir * ms
+                    } else {
+                        Vec2::ZERO
+                    }
+                }
+                _ => {
+                    self.enter(BehaviourPhase::Approach);
+                    dir * ms
+                }
+            },
+            EnemyBehaviour::Swarmer(swarmer) => match self.phase {
+                BehaviourPhase::Approach => {
+                    if to_target.length() <= swarmer.orbit_radius * 1.2 {
+                        self.enter(BehaviourPhase::Orbit((-to_target).to_angle()));
+                    }
+                    dir * ms
+                }
+                BehaviourPhase::Orbit(ref mut angle) => {
+                    if self.timer >= swarmer.orbit_time {
+                        self.enter(BehaviourPhase::Dive(dir));
+                        dir * ms * swarmer.dive_speed
+                    } else {
+                        *angle += swarmer.orbit_speed * dt;
+                        let wanted = t_pos + Vec2::from_angle(*angle) * swarmer.orbit_radius;
+                        ((wanted - e_pos) * ORBIT_CORRECTION).clamp_length_max(ms)
+                    }
+                }
+                BehaviourPhase::Dive(dive_dir) => {
+                    if self.timer >= swarmer.dive_time {
+                        self.enter(BehaviourPhase::Approach);
+                        dir * ms
+                    } else {
+                        dive_dir * ms * swarmer.dive_speed
+                    }
+                }
+                _ => {
+                    self.enter(BehaviourPhase::Approach);
+                    dir * ms
+                }
+            },
+            _ => return None,
+        };
+        Some(velo)
+    }
+}
+
+/// Runs right after `enemy_state_machine`, and takes over the velocity of anything that's
+/// chasing a target and has an archetype with its own movement
+pub fn enemy_behaviours<EnemyQF: QueryFilter, PlayerQF: QueryFilter>(
+    time: Res<Time<Fixed>>,
+    mut q_enemy: Query<
+        (
+            &Enemy,
+            &EnemyBehaviour,
+            &mut EnemyBehaviourState,
+            &Position,
+            &mut LinearVelocity,
+            &MovementSpeed,
+        ),
+        // A frozen enemy's behaviour picks back up where it left off
+        (Without<Dead>, Without<Frozen>, EnemyQF),
+    >,
+    q_targets: Query<&Position, (TargetablePlayer, PlayerQF)>,
+) {
+    for (enemy, behaviour, mut state, e_pos, mut e_lv, e_ms) in &mut q_enemy {
+        let EnemyState::MovingTo(target) = enemy.state else {
+            continue;
+        };
+        let Ok(t_pos) = q_targets.get(target) else {
+            continue;
+        };
+        if let Some(velo) = state.step(behaviour, e_pos.0, t_pos.0, e_ms.current, time.delta_secs())
+        {
+            e_lv.0 = velo;
+        }
+    }
+}
+
+/// Spawns the children of a splitter that just died. They're spread evenly around where it died,
+/// so there's nothing random about where they end up
+pub fn spawn_splitter_children(
+    commands: &mut Commands,
+    registry: &EnemyRegistry,
+    game_kind: GameKinds,
+    splitter: &SplitterBehaviour,
+    pos: Vec2,
+    difficulty: &DifficultyModifiers,
+) {
+    let Some(child) = registry.kind(&splitter.child) else {
+        warn!("Splitter child {:?} isn't a known enemy", splitter.child);
+        return;
+    };
+    for i in 0..splitter.count {
+        let angle = std::f32::consts::TAU * (i as f32 / splitter.count as f32);
+        let child_pos = pos + Vec2::from_angle(angle) * splitter.spread;
+        spawn_enemy(
+            commands, registry, child, game_kind, child_pos, difficulty, None,
+        );
+    }
+}
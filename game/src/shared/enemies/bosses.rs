@@ -0,0 +1,206 @@
+//! Bosses: enemies with a lot of health that show up at set times in the match, change up how
+//! they fight as they get hurt, and always leave a big reward behind.
+//!
+//! A boss is just an enemy whose definition has a `boss` section. The enemy's own `behaviour` is
+//! its first phase, and each entry in `phases` takes over once the boss drops below that much
+//! of its health. When the boss turns up is decided by `assets/game_rules/boss_schedule.ron`
+use rand::Rng;
+
+use super::*;
+use crate::shared::{
+    enemies::{behaviours::EnemyBehaviourState, targeting::TargetablePlayer},
+    map::CurrentMap,
+    match_clock::MatchClock,
+    rng::{MatchRng, RngStream},
+    stats::components::Health,
+};
+
+/// Bosses show up a bit further out than everything else, so there's a moment to see them coming
+const BOSS_SPAWN_DISTANCE: f32 = 600.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct BossDefinition {
+    /// These have to go from the highest health threshold to the lowest
+    pub phases: Vec<BossPhase>,
+    /// The XP in the gem that the boss always drops, before the difficulty gets involved
+    pub reward_xp: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct BossPhase {
+    /// The fraction of max health that the boss has to drop to for this phase to start
+    pub below_health: f32,
+    pub behaviour: EnemyBehaviour,
+}
+
+impl BossDefinition {
+    /// The phase that a boss with this fraction of its health left should be in. Phase 0 is
+    /// the enemy's own behaviour, before any of `phases` have kicked in
+    pub fn phase_for(&self, health_fraction: f32) -> u8 {
+        self.phases
+            .iter()
+            .take_while(|phase| health_fraction <= phase.below_health)
+            .count() as u8
+    }
+
+    pub fn behaviour<'a>(&'a self, base: &'a EnemyBehaviour, phase: u8) -> &'a EnemyBehaviour {
+        match phase {
+            0 => base,
+            n => &self.phases[(n as usize - 1).min(self.phases.len() - 1)].behaviour,
+        }
+    }
+}
+
+/// Marks an enemy as a boss, so the HUD can find it, and tracks which phase it's on.
+///
+/// This is predicted, since the phase decides the behaviour, which is predicted too
+#[derive(Component, Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Boss {
+    pub phase: u8,
+}
+
+/// When each boss turns up, as read from `assets/game_rules/boss_schedule.ron`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Reflect)]
+pub struct BossSchedule {
+    pub encounters: Vec<BossEncounter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
+pub struct BossEncounter {
+    /// How far into the match (in minutes) the boss shows up
+    pub at_mins: f32,
+    /// The folder of the boss under `assets/enemies`
+    pub boss: String,
+}
+
+impl BossSchedule {
+    pub const PATH: &'static str = "assets/game_rules/boss_schedule.ron";
+
+    pub fn import() -> Self {
+        let mut schedule = crate::utils::read_ron::<BossSchedule>(Self::PATH.into());
+        schedule
+            .encounters
+            .sort_by(|a, b| a.at_mins.total_cmp(&b.at_mins));
+        schedule
+    }
+}
+
+/// Works through the boss schedule over the course of the match
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct BossDirector {
+    pub schedule: BossSchedule,
+    /// The next encounter in the schedule that hasn't happened yet
+    pub next: usize,
+}
+
+pub fn spawn_boss_director(mut commands: Commands) {
+    commands.insert_resource(BossDirector {
+        schedule: BossSchedule::import(),
+        next: 0,
+    });
+}
+
+pub fn update_boss_director<QF: QueryFilter>(
+    mut commands: Commands,
+    mut director: ResMut<BossDirector>,
+    game_kinds: Res<CurrentGameKind>,
+    difficulty: Res<DifficultyModifiers>,
+    registry: Res<EnemyRegistry>,
+    map: Res<CurrentMap>,
+    mut match_rng: ResMut<MatchRng>,
+    q_clock: Query<&MatchClock, QF>,
+    q_players: Query<&Position, (TargetablePlayer, QF)>,
+) {
+    let Some(clock) = q_clock.iter().next() else {
+        return;
+    };
+    let player_positions: Vec<Vec2> = q_players.iter().map(|p| p.0).collect();
+    if player_positions.is_empty() {
+        return;
+    }
+    let rng = match_rng.stream(RngStream::Bosses);
+    while let Some(encounter) = director.schedule.encounters.get(director.next) {
+        if clock.elapsed_mins() < encounter.at_mins {
+            break;
+        }
+        match registry.kind(&encounter.boss) {
+            Some(kind) => {
+                let center = player_positions[rng.random_range(0..player_positions.len())];
+                // Bosses have to show up, so fall back to anywhere in bounds if the area around
+                // the player is no good
+                let pos = map
+                    .spawn_point_near(center, BOSS_SPAWN_DISTANCE, BOSS_SPAWN_DISTANCE, rng)
+                    .unwrap_or_else(|| map.clamp(center + Vec2::X * BOSS_SPAWN_DISTANCE));
+                spawn_enemy(
+                    &mut commands,
+                    &registry,
+                    kind,
+                    game_kinds.0.unwrap(),
+                    pos,
+                    &difficulty,
+                    None,
+                );
+            }
+            None => warn!(
+                "The boss schedule has {:?}, which isn't an enemy",
+                encounter.boss
+            ),
+        }
+        director.next += 1;
+    }
+}
+
+/// Moves bosses on to their next phase as they lose health. Bosses never go back to an earlier
+/// phase, even if they get healed
+pub fn update_boss_phases<QF: QueryFilter>(
+    registry: Res<EnemyRegistry>,
+    mut q_boss: Query<(&Enemy, &mut Boss, &Health), (Without<Dead>, QF)>,
+) {
+    for (enemy, mut boss, health) in &mut q_boss {
+        let Some(boss_def) = &registry.get(enemy.kind).boss else {
+            continue;
+        };
+        let phase = boss_def.phase_for(health.current / health.max);
+        if phase > boss.phase {
+            boss.phase = phase;
+        }
+    }
+}
+
+/// The phase whose behaviour a boss actually has right now. Nothing here means it still has the
+/// behaviour it spawned with, which is phase 0.
+///
+/// `Boss` gets marked as changed every time replication or a rollback writes it, even when the
+/// phase is the same, so this is what tells a real change of phase apart from that
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+pub struct AppliedBossPhase(pub u8);
+
+/// Swaps out the behaviour of a boss whenever its phase changes, whether that was decided here
+/// or came in from the server
+pub fn apply_boss_phase<QF: QueryFilter>(
+    mut commands: Commands,
+    registry: Res<EnemyRegistry>,
+    q_boss: Query<(Entity, &Enemy, &Boss, Option<&AppliedBossPhase>), (Changed<Boss>, QF)>,
+) {
+    for (ent, enemy, boss, m_applied) in &q_boss {
+        if m_applied.map_or(0, |a| a.0) == boss.phase {
+            continue;
+        }
+        let def = registry.get(enemy.kind);
+        let Some(boss_def) = &def.boss else {
+            continue;
+        };
+        let behaviour = boss_def.behaviour(&def.behaviour, boss.phase);
+        let mut e_commands = commands.entity(ent);
+        e_commands.insert((behaviour.clone(), AppliedBossPhase(boss.phase)));
+        match behaviour.ranged_attack() {
+            Some(ranged) => e_commands.insert(ranged),
+            None => e_commands.remove::<RangedAttack>(),
+        };
+        match behaviour.initial_state() {
+            Some(state) => e_commands.insert(state),
+            None => e_commands.remove::<EnemyBehaviourState>(),
+        };
+    }
+}
@@ -0,0 +1,227 @@
+//! Everything about an enemy that designers should be able to change without touching Rust.
+//!
+//! Each folder under `assets/enemies` that has an `enemy.ron` in it is an enemy. Every definition
+//! declares its own `id`, which is what an `EnemyKind` holds, so adding or renaming a folder
+//! doesn't change what any of the existing kinds are (demos and the network both rely on that).
+//! New enemies should take the next id that isn't in use, and never reuse an old one
+use super::*;
+use crate::shared::enemies::{behaviours::*, bosses::BossDefinition};
+
+/// The folder that all of the enemy definitions live under
+pub const ENEMIES_FOLDER: &str = "assets/enemies";
+pub const DEFINITION_FILE: &str = "enemy.ron";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
+pub struct EnemyDefinition {
+    /// Has to be unique across every enemy, and shouldn't change once the enemy exists
+    pub id: EnemyKind,
+    pub name: String,
+    pub stats: RawStatsList,
+    pub hitbox: EnemyHitbox,
+    pub mass: f32,
+    pub sprite: EnemySprite,
+    pub behaviour: EnemyBehaviour,
+    /// How much XP the enemy leaves behind, before the difficulty gets involved
+    pub xp_drop: f32,
+    pub spawning: EnemySpawnRules,
+    /// Only bosses have this
+    #[serde(default)]
+    pub boss: Option<BossDefinition>,
+}
+
+impl EnemyDefinition {
+    pub fn collider_bundle(&self) -> CommonColliderBundle {
+        CommonColliderBundle::new(
+            RigidBody::Dynamic,
+            self.hitbox.to_collider(),
+            self.mass,
+            [ColliderTypes::Enemy].into(),
+            // Enemies don't collide with each other, separation steering keeps them apart
+            // without making the solver deal with a crowd of contacts
+            [
+                ColliderTypes::Player,
+                ColliderTypes::PlayerProjectile,
+                ColliderTypes::Obstacle,
+                ColliderTypes::Prop,
+            ]
+            .into(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum EnemyHitbox {
+    Capsule { radius: f32, length: f32 },
+    Circle { radius: f32 },
+    Rectangle { width: f32, height: f32 },
+}
+
+impl EnemyHitbox {
+    pub fn to_collider(&self) -> Collider {
+        match *self {
+            Self::Capsule { radius, length } => Collider::capsule(radius, length),
+            Self::Circle { radius } => Collider::circle(radius),
+            Self::Rectangle { width, height } => Collider::rectangle(width, height),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct EnemySprite {
+    /// Relative to the enemy's folder
+    pub image: String,
+    /// Leave this out for a sprite that's just a single image
+    pub animation: Option<EnemyAnimation>,
+}
+
+/// The layout of a sprite sheet, with one row per facing (Down, Right, Up, Left)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct EnemyAnimation {
+    pub tile_size: UVec2,
+    pub columns: u32,
+    pub rows: u32,
+    pub fps: u8,
+}
+
+/// The archetype that decides how an enemy goes about trying to hurt the players.
+///
+/// This also gets put on the enemy as a component on both the server and the client, so that
+/// the behaviour layer can be predicted
+#[derive(Component, Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum EnemyBehaviour {
+    /// Walks straight at its target
+    Melee,
+    /// Keeps its distance and fires at its target
+    Ranged(RangedAttack),
+    Charger(ChargerBehaviour),
+    Splitter(SplitterBehaviour),
+    Swarmer(SwarmerBehaviour),
+}
+
+impl EnemyBehaviour {
+    pub fn ranged_attack(&self) -> Option<RangedAttack> {
+        match self {
+            Self::Ranged(ranged) => Some(*ranged),
+            _ => None,
+        }
+    }
+
+    /// Archetypes that move on their own need a state to keep track of where they're at
+    pub fn initial_state(&self) -> Option<EnemyBehaviourState> {
+        match self {
+            Self::Charger(_) | Self::Swarmer(_) => Some(EnemyBehaviourState::default()),
+            _ => None,
+        }
+    }
+}
+
+/// How the spawn director treats this enemy
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct EnemySpawnRules {
+    /// How likely this enemy is to be picked, relative to the others that can spawn
+    pub weight: f32,
+    /// How far into the match (in minutes) before this enemy starts showing up
+    pub after_mins: f32,
+}
+
+/// All of the enemies that were found under `assets/enemies`, sorted by their ids
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct EnemyRegistry {
+    folders: Vec<String>,
+    definitions: Vec<EnemyDefinition>,
+}
+
+impl EnemyRegistry {
+    pub fn load() -> Self {
+        Self::load_from(ENEMIES_FOLDER)
+    }
+
+    /// Panics if two of the enemies have the same id
+    pub fn load_from(root: &str) -> Self {
+        let mut enemies: Vec<(String, EnemyDefinition)> = std::fs::read_dir(root)
+            .unwrap_or_else(|_| panic!("Failed to read enemies folder {:?}", root))
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join(DEFINITION_FILE).is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .map(|folder| {
+                let def = crate::utils::read_ron::<EnemyDefinition>(format!(
+                    "{}/{}/{}",
+                    root, folder, DEFINITION_FILE
+                ));
+                (folder, def)
+            })
+            .collect();
+        enemies.sort_by_key(|(_, def)| def.id);
+        for pair in enemies.windows(2) {
+            if let [(a, a_def), (b, b_def)] = pair
+                && a_def.id == b_def.id
+            {
+                panic!(
+                    "Enemies {a:?} and {b:?} both have the id {}, but ids have to be unique",
+                    a_def.id.0
+                );
+            }
+        }
+
+        let (folders, definitions) = enemies.into_iter().unzip();
+        Self {
+            folders,
+            definitions,
+        }
+    }
+
+    fn index(&self, kind: EnemyKind) -> usize {
+        self.definitions
+            .binary_search_by_key(&kind, |def| def.id)
+            .unwrap_or_else(|_| {
+                panic!(
+                    "No enemy has the id {}, so the assets don't match whatever spawned it",
+                    kind.0
+                )
+            })
+    }
+
+    pub fn contains(&self, kind: EnemyKind) -> bool {
+        self.definitions
+            .binary_search_by_key(&kind, |def| def.id)
+            .is_ok()
+    }
+
+    pub fn get(&self, kind: EnemyKind) -> &EnemyDefinition {
+        &self.definitions[self.index(kind)]
+    }
+
+    pub fn get_mut(&mut self, kind: EnemyKind) -> &mut EnemyDefinition {
+        let idx = self.index(kind);
+        &mut self.definitions[idx]
+    }
+
+    /// Looks up a kind by the name of its folder, like "faceless"
+    pub fn kind(&self, folder: &str) -> Option<EnemyKind> {
+        self.folders
+            .iter()
+            .position(|f| f == folder)
+            .map(|idx| self.definitions[idx].id)
+    }
+
+    pub fn folder(&self, kind: EnemyKind) -> AssetFolder {
+        AssetFolder(format!("enemies/{}", self.folders[self.index(kind)]))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EnemyKind, &EnemyDefinition)> {
+        self.definitions.iter().map(|def| (def.id, def))
+    }
+
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+pub fn load_enemy_registry(mut commands: Commands) {
+    commands.insert_resource(EnemyRegistry::load());
+}
@@ -0,0 +1,215 @@
+//! Elites: regular enemies that get promoted when they spawn, with tougher stats, better drops
+//! and one or more affixes.
+//!
+//! How often that happens, what it does to their stats, and what each affix does are all read
+//! from `assets/game_rules/elites.ron`. The rolled `Elite` is replicated along with the enemy, so
+//! clients don't need the table to render or predict one
+use rand::Rng;
+
+use super::*;
+use crate::shared::{
+    damage::{AppliedDamageMessage, DamageBuffer, DamageInstance},
+    stats::components::{Damage, Health},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct StatMultipliers {
+    pub health: f32,
+    pub damage: f32,
+    pub speed: f32,
+    /// Scales the XP that the enemy drops
+    pub xp: f32,
+}
+
+impl Default for StatMultipliers {
+    fn default() -> Self {
+        Self {
+            health: 1.0,
+            damage: 1.0,
+            speed: 1.0,
+            xp: 1.0,
+        }
+    }
+}
+
+impl StatMultipliers {
+    pub fn combine(&self, other: &StatMultipliers) -> Self {
+        Self {
+            health: self.health * other.health,
+            damage: self.damage * other.damage,
+            speed: self.speed * other.speed,
+            xp: self.xp * other.xp,
+        }
+    }
+
+    /// Scales the stats that an enemy is about to be spawned with
+    pub fn apply_to_enemy_stats(&self, stats: &mut RawStatsList) {
+        stats.scale_enemy_stats(self.health, self.damage, self.speed);
+    }
+}
+
+/// Anything an affix does on top of changing stats lives in its variant
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum EliteAffix {
+    Hasted,
+    Armored,
+    /// Heals for this fraction of the contact damage it deals
+    Vampiric {
+        lifesteal: f32,
+    },
+    /// Blows up when it dies, hurting any players in range for a multiple of its own damage
+    Explosive {
+        radius: f32,
+        damage: f32,
+    },
+}
+
+impl EliteAffix {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hasted => "Hasted",
+            Self::Armored => "Armored",
+            Self::Vampiric { .. } => "Vampiric",
+            Self::Explosive { .. } => "Explosive",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct EliteAffixDefinition {
+    pub affix: EliteAffix,
+    pub stats: StatMultipliers,
+    /// How likely this affix is to be picked, relative to the others
+    pub weight: f32,
+}
+
+/// As read from `assets/game_rules/elites.ron`
+#[derive(Resource, Debug, Clone, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct EliteTable {
+    /// The chance that an enemy gets promoted at the start of the match
+    pub chance: f32,
+    /// How much that chance goes up every minute
+    pub chance_per_min: f32,
+    pub max_chance: f32,
+    pub max_affixes: u8,
+    /// What every elite gets, no matter its affixes
+    pub base: StatMultipliers,
+    pub affixes: Vec<EliteAffixDefinition>,
+}
+
+impl EliteTable {
+    pub const PATH: &'static str = "assets/game_rules/elites.ron";
+
+    pub fn import() -> Self {
+        crate::utils::read_ron::<EliteTable>(Self::PATH.into())
+    }
+
+    pub fn chance_at(&self, elapsed_mins: f32) -> f32 {
+        (self.chance + self.chance_per_min * elapsed_mins).clamp(0.0, self.max_chance.min(1.0))
+    }
+
+    /// Decides whether an enemy spawning now should be an elite, and which affixes it gets
+    pub fn roll(&self, elapsed_mins: f32, rng: &mut impl Rng) -> Option<Elite> {
+        if self.affixes.is_empty() || !rng.random_bool(self.chance_at(elapsed_mins) as f64) {
+            return None;
+        }
+        let n_affixes = rng.random_range(1..=self.max_affixes.max(1)) as usize;
+        let mut available: Vec<&EliteAffixDefinition> = self.affixes.iter().collect();
+        let mut elite = Elite {
+            affixes: Vec::new(),
+            stats: self.base,
+        };
+        // Affixes are picked without replacement, so nothing gets the same one twice
+        while elite.affixes.len() < n_affixes && !available.is_empty() {
+            let total: f32 = available.iter().map(|a| a.weight).sum();
+            if total <= 0.0 {
+                break;
+            }
+            let mut pick = rng.random_range(0.0..total);
+            let idx = available
+                .iter()
+                .position(|a| {
+                    pick -= a.weight;
+                    pick < 0.0
+                })
+                .unwrap_or(available.len() - 1);
+            let def = available.remove(idx);
+            elite.affixes.push(def.affix);
+            elite.stats = elite.stats.combine(&def.stats);
+        }
+        Some(elite)
+    }
+}
+
+/// An enemy that got promoted. The stats have already been applied by the time this is on
+/// an enemy, so this is mostly for the affixes that do something, and for rendering
+#[derive(Component, Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Elite {
+    pub affixes: Vec<EliteAffix>,
+    pub stats: StatMultipliers,
+}
+
+impl Elite {
+    pub fn lifesteal(&self) -> f32 {
+        self.affixes
+            .iter()
+            .map(|a| match a {
+                EliteAffix::Vampiric { lifesteal } => *lifesteal,
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    pub fn explosion(&self) -> Option<(f32, f32)> {
+        self.affixes.iter().find_map(|a| match a {
+            EliteAffix::Explosive { radius, damage } => Some((*radius, *damage)),
+            _ => None,
+        })
+    }
+}
+
+pub fn load_elite_table(mut commands: Commands) {
+    commands.insert_resource(EliteTable::import());
+}
+
+/// Vampiric elites heal off of the damage they deal to players
+pub fn elite_lifesteal<QF: QueryFilter>(
+    mut damage: MessageReader<AppliedDamageMessage>,
+    q_player: Query<(), With<Player>>,
+    mut q_elite: Query<(&Elite, &mut Health), (Without<Dead>, QF)>,
+) {
+    for hit in damage.read() {
+        if !q_player.contains(hit.target) {
+            continue;
+        }
+        let Ok((elite, mut health)) = q_elite.get_mut(hit.source) else {
+            continue;
+        };
+        health.current = (health.current + hit.amount * elite.lifesteal()).min(health.max);
+    }
+}
+
+/// Explosive elites hurt every player close enough when they die. The damage scales off of the
+/// elite's own damage, so difficulty and the elite multipliers carry over
+pub fn on_elite_death<QF: QueryFilter>(
+    trigger: On<Add, Dead>,
+    q_elite: Query<(&Elite, &Position, &Damage), QF>,
+    mut q_players: Query<(&Position, &mut DamageBuffer), (With<Player>, Without<Enemy>, QF)>,
+) {
+    let Ok((elite, e_pos, dam)) = q_elite.get(trigger.entity) else {
+        return;
+    };
+    let Some((radius, mult)) = elite.explosion() else {
+        return;
+    };
+    for (p_pos, mut buffer) in &mut q_players {
+        if p_pos.0.distance(e_pos.0) <= radius {
+            buffer.push(DamageInstance {
+                damage_source: trigger.entity,
+                amount: dam.0 * mult,
+                crit: false,
+            });
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Level of detail for enemy physics.
+//!
+//! Enemies a long way from every player drop out of the physics solver entirely, and just move
+//! along their velocity instead. That only happens off screen, past the reach of anything a
+//! player has, so nothing out there could touch them anyway, and it keeps the cost of the
+//! solver tied to what's on screen rather than to the whole horde. Once they get close
+//! again, they're put back into the solver. Both sides make this call from the same replicated
+//! positions, and `Position` and `LinearVelocity` keep working the same way either way, so
+//! none of this has to be networked
+use super::*;
+use crate::shared::players::VIEW_EXTENT;
+
+/// How far past the corner of the screen an enemy has to be before it can be simplified, for
+/// anything a player has that reaches further than they can see (like orbiting projectiles
+/// that have grown)
+pub const LOD_MARGIN: f32 = 300.0;
+/// Under this far from the nearest player, enemies use the full physics solver. Going across
+/// and then up is always at least as far as the diagonal, so this is off screen in every
+/// direction
+pub const LOD_NEAR: f32 = VIEW_EXTENT.x + VIEW_EXTENT.y + LOD_MARGIN;
+/// Past this far, they stop using it. The gap keeps enemies right on the line from flipping
+/// back and forth
+pub const LOD_FAR: f32 = LOD_NEAR + 100.0;
+
+/// This enemy is out of the solver, and is being moved by `integrate_simplified_enemies`
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+pub struct SimplifiedPhysics;
+
+/// Whether an enemy should be simplified, given whether it is now and how far away the nearest
+/// player is
+pub fn should_simplify(simplified: bool, nearest_player: Option<f32>) -> bool {
+    match nearest_player {
+        // Nobody to get close to
+        None => true,
+        Some(dist) if simplified => dist > LOD_NEAR,
+        Some(dist) => dist > LOD_FAR,
+    }
+}
+
+pub fn update_enemy_lod<EnemyQF: QueryFilter, PlayerQF: QueryFilter>(
+    mut commands: Commands,
+    q_enemy: Query<(Entity, &Position, Has<SimplifiedPhysics>), (With<Enemy>, EnemyQF)>,
+    q_players: Query<&Position, (With<Player>, Without<Enemy>, Without<Dead>, PlayerQF)>,
+) {
+    // There's only ever a handful of players, so this is cheaper than asking the spatial index
+    let players: Vec<Vec2> = q_players.iter().map(|p| p.0).collect();
+    for (ent, pos, simplified) in &q_enemy {
+        let nearest = players
+            .iter()
+            .map(|p| p.distance(pos.0))
+            .min_by(f32::total_cmp);
+        match (simplified, should_simplify(simplified, nearest)) {
+            (false, true) => {
+                commands.entity(ent).insert((
+                    SimplifiedPhysics,
+                    RigidBodyDisabled,
+                    ColliderDisabled,
+                ));
+            }
+            (true, false) => {
+                commands
+                    .entity(ent)
+                    .remove::<(SimplifiedPhysics, RigidBodyDisabled, ColliderDisabled)>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Stands in for the solver for anything that's been simplified
+pub fn integrate_simplified_enemies<QF: QueryFilter>(
+    time: Res<Time<Fixed>>,
+    mut q_enemy: Query<(&mut Position, &LinearVelocity), (With<SimplifiedPhysics>, QF)>,
+) {
+    for (mut pos, lv) in &mut q_enemy {
+        pos.0 += lv.0 * time.delta_secs();
+    }
+}
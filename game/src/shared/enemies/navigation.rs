@@ -0,0 +1,221 @@
+//! How enemies get around: a flow field per player, and a bit of separation so that crowds
+//! spread out on their own instead of piling up in the physics solver.
+//!
+//! Each field is a grid centered on a player, where every cell knows how far it is from that
+//! player going around any `NavObstacle`s. An enemy chasing somebody just looks at the cells
+//! around it and heads for the cheapest one. The fields get rebuilt every so often rather than
+//! every tick, since players don't move that far in a quarter of a second
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::platform::collections::HashMap;
+
+use super::*;
+use crate::shared::spatial_index::SpatialIndex;
+
+pub const FLOW_CELL_SIZE: f32 = 32.0;
+/// How far out from the player the field reaches. Past this, enemies just head straight for them
+pub const FLOW_FIELD_HALF_EXTENT: f32 = 1024.0;
+pub const FLOW_FIELD_REFRESH: f32 = 0.25;
+/// Enemies closer together than this start pushing each other apart
+pub const SEPARATION_RADIUS: f32 = 40.0;
+/// How much separation counts for next to following the field
+pub const SEPARATION_WEIGHT: f32 = 0.75;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const NEIGHBOURS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+/// Something that enemies have to path around. It needs a collider, since the field is built
+/// from the collider's bounding box
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+pub struct NavObstacle;
+
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    /// The cell in the bottom left corner of the field
+    origin: IVec2,
+    size: IVec2,
+    cell_size: f32,
+    /// The cost to get from each cell to the target. `u32::MAX` is blocked or unreachable
+    costs: Vec<u32>,
+}
+
+impl FlowField {
+    /// Builds a field leading to `target`, going around anything in `obstacles`
+    pub fn new(target: Vec2, half_extent: f32, cell_size: f32, obstacles: &[Rect]) -> Self {
+        let target_cell = (target / cell_size).floor().as_ivec2();
+        let half_cells = (half_extent / cell_size).ceil() as i32;
+        let mut field = Self {
+            origin: target_cell - IVec2::splat(half_cells),
+            size: IVec2::splat(half_cells * 2 + 1),
+            cell_size,
+            costs: vec![u32::MAX; ((half_cells * 2 + 1) * (half_cells * 2 + 1)) as usize],
+        };
+
+        let blocked: Vec<bool> = (0..field.costs.len())
+            .map(|i| {
+                let rect = field.cell_rect(field.cell_at(i));
+                obstacles.iter().any(|o| !o.intersect(rect).is_empty())
+            })
+            .collect();
+
+        // Dijkstra out from the target. The target's own cell is never blocked, otherwise a
+        // player standing against a wall would be impossible to reach
+        let mut open = BinaryHeap::new();
+        let start = field.index(target_cell).unwrap();
+        field.costs[start] = 0;
+        open.push(Reverse((0, start)));
+        while let Some(Reverse((cost, i))) = open.pop() {
+            if cost > field.costs[i] {
+                continue;
+            }
+            let cell = field.cell_at(i);
+            for offset in NEIGHBOURS {
+                let Some(n) = field.index(cell + offset) else {
+                    continue;
+                };
+                if blocked[n] {
+                    continue;
+                }
+                let step = if offset.x != 0 && offset.y != 0 {
+                    // No cutting corners around obstacles
+                    let side_a = field.index(cell + IVec2::new(offset.x, 0));
+                    let side_b = field.index(cell + IVec2::new(0, offset.y));
+                    if side_a.is_none_or(|a| blocked[a]) || side_b.is_none_or(|b| blocked[b]) {
+                        continue;
+                    }
+                    DIAGONAL_COST
+                } else {
+                    STRAIGHT_COST
+                };
+                if cost + step < field.costs[n] {
+                    field.costs[n] = cost + step;
+                    open.push(Reverse((cost + step, n)));
+                }
+            }
+        }
+        field
+    }
+
+    /// Which way to go from `pos` to get closer to the target.
+    ///
+    /// This is `None` in the target's own cell, and anywhere the field doesn't cover or can't
+    /// reach. In those cases the enemy should just head straight for the target. An enemy that
+    /// has been pushed into a cell that's partly blocked gets led back out to the nearest open one
+    pub fn direction(&self, pos: Vec2) -> Option<Vec2> {
+        let cell = (pos / self.cell_size).floor().as_ivec2();
+        let here = self.costs[self.index(cell)?];
+        if here == 0 {
+            return None;
+        }
+        let best = NEIGHBOURS
+            .iter()
+            .filter_map(|offset| Some((*offset, self.costs[self.index(cell + *offset)?])))
+            .filter(|(_, cost)| *cost != u32::MAX)
+            .min_by_key(|(_, cost)| *cost)?;
+        (best.1 < here).then(|| best.0.as_vec2().normalize())
+    }
+
+    /// The cost of getting to the target from `pos`, if it's reachable
+    pub fn cost(&self, pos: Vec2) -> Option<u32> {
+        let cell = (pos / self.cell_size).floor().as_ivec2();
+        self.index(cell)
+            .map(|i| self.costs[i])
+            .filter(|c| *c != u32::MAX)
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        let local = cell - self.origin;
+        (local.x >= 0 && local.y >= 0 && local.x < self.size.x && local.y < self.size.y)
+            .then_some((local.y * self.size.x + local.x) as usize)
+    }
+
+    fn cell_at(&self, i: usize) -> IVec2 {
+        self.origin + IVec2::new(i as i32 % self.size.x, i as i32 / self.size.x)
+    }
+
+    fn cell_rect(&self, cell: IVec2) -> Rect {
+        let min = cell.as_vec2() * self.cell_size;
+        Rect::from_corners(min, min + Vec2::splat(self.cell_size))
+    }
+}
+
+/// One flow field for every player that can be chased
+#[derive(Resource, Debug)]
+pub struct FlowFields {
+    pub fields: HashMap<Entity, FlowField>,
+    pub refresh: Timer,
+}
+
+impl Default for FlowFields {
+    fn default() -> Self {
+        Self {
+            fields: HashMap::default(),
+            refresh: Timer::from_seconds(FLOW_FIELD_REFRESH, TimerMode::Repeating),
+        }
+    }
+}
+
+impl FlowFields {
+    pub fn direction(&self, target: Entity, pos: Vec2) -> Option<Vec2> {
+        self.fields.get(&target)?.direction(pos)
+    }
+}
+
+/// Pushes away from any neighbours inside of `SEPARATION_RADIUS`, harder the closer they are.
+///
+/// The result is at most 1 long, so it can be weighed against a normalized heading
+pub fn separation_force(pos: Vec2, neighbours: impl Iterator<Item = Vec2>) -> Vec2 {
+    neighbours
+        .filter_map(|other| {
+            let away = pos - other;
+            let dist = away.length();
+            // Two enemies right on top of each other have no way to tell which way is apart.
+            // That never lasts long, since they'll be following slightly different headings
+            (dist > f32::EPSILON && dist < SEPARATION_RADIUS)
+                .then(|| away / dist * (1.0 - dist / SEPARATION_RADIUS))
+        })
+        .sum::<Vec2>()
+        .clamp_length_max(1.0)
+}
+
+/// The separation force on an enemy from every other enemy near it
+pub fn enemy_separation(index: &SpatialIndex, ent: Entity, pos: Vec2) -> Vec2 {
+    separation_force(
+        pos,
+        index
+            .within_radius(pos, SEPARATION_RADIUS, ColliderTypes::Enemy)
+            .filter(|e| e.entity != ent)
+            .map(|e| e.pos),
+    )
+}
+
+pub fn update_flow_fields<PlayerQF: QueryFilter>(
+    time: Res<Time<Fixed>>,
+    mut flow: ResMut<FlowFields>,
+    q_players: Query<(Entity, &Position), (TargetablePlayer, PlayerQF)>,
+    q_obstacles: Query<&ColliderAabb, With<NavObstacle>>,
+) {
+    flow.refresh.tick(time.delta());
+    let refresh_all = flow.refresh.just_finished();
+    flow.fields.retain(|ent, _| q_players.contains(*ent));
+    let obstacles: Vec<Rect> = q_obstacles
+        .iter()
+        .map(|aabb| Rect::from_corners(aabb.min, aabb.max))
+        .collect();
+    for (ent, pos) in &q_players {
+        if refresh_all || !flow.fields.contains_key(&ent) {
+            let field = FlowField::new(pos.0, FLOW_FIELD_HALF_EXTENT, FLOW_CELL_SIZE, &obstacles);
+            flow.fields.insert(ent, field);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Enemies that keep their distance and throw things at the players
+use super::*;
+use crate::shared::{
+    combat::Cooldown,
+    despawn_timer::DespawnTimer,
+    projectiles::{Projectile, ProjectileFaction, ProjectileMovement},
+    stats::components::Damage,
+};
+
+/// How an enemy attacks from range. Not networked, since it's decided by the enemy's definition
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct RangedAttack {
+    /// How close the target has to be before the enemy will fire at it
+    pub range: f32,
+    /// The distance the enemy tries to hold from its target
+    pub keep_away: f32,
+    /// Seconds between shots
+    pub cooldown: f32,
+    pub projectile_speed: f32,
+    /// Seconds before a projectile that hasn't hit anything goes away
+    pub projectile_lifetime: f32,
+}
+
+impl RangedAttack {
+    /// Scales how hard the enemy moves towards its target, given how far away it is.
+    ///
+    /// Negative means it's backing off, and there's a band around `keep_away` where it stands still
+    pub fn approach(&self, dist: f32) -> f32 {
+        if dist > self.range {
+            1.0
+        } else if dist < self.keep_away {
+            -1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Fires at the target once it's in range, as long as the enemy is off cooldown.
+///
+/// This only runs where the game is actually being simulated, and the projectiles are replicated
+pub fn enemy_ranged_attack<EnemyQF: QueryFilter, PlayerQF: QueryFilter>(
+    mut commands: Commands,
+    gk: Res<CurrentGameKind>,
+    q_enemy: Query<
+        (Entity, &Enemy, &Position, &RangedAttack, &Damage),
+        (Without<Cooldown>, Without<Dead>, Without<Frozen>, EnemyQF),
+    >,
+    q_targets: Query<&Position, (TargetablePlayer, PlayerQF)>,
+) {
+    for (ent, enemy, e_pos, ranged, dam) in &q_enemy {
+        let EnemyState::MovingTo(target) = enemy.state else {
+            continue;
+        };
+        let Ok(t_pos) = q_targets.get(target) else {
+            continue;
+        };
+        let to_target = t_pos.0 - e_pos.0;
+        if to_target.length() > ranged.range {
+            continue;
+        }
+        let proj = Projectile {
+            movement: ProjectileMovement::Linear(
+                to_target.normalize_or_zero() * ranged.projectile_speed,
+            ),
+            faction: ProjectileFaction::Enemy,
+        };
+        spawn_game_object(
+            &mut commands,
+            gk.0.unwrap(),
+            MultiPlayerComponentOptions::from(proj),
+            (
+                proj,
+                Position(e_pos.0),
+                *dam,
+                AppliesCollisionEffect::new([ColliderTypes::Player].into(), ApplyDamage),
+                DespawnTimer::new(ranged.projectile_lifetime),
+            ),
+        );
+        commands.entity(ent).insert(Cooldown::new(ranged.cooldown));
+    }
+}
@@ -1,10 +1,41 @@
 use super::*;
 use bevy::prelude::*;
+use rand::Rng;
 
-#[derive(Resource, Default, Reflect)]
+use crate::shared::{
+    enemies::{definitions::EnemyRegistry, elites::EliteTable},
+    map::CurrentMap,
+    match_clock::MatchClock,
+    rng::{MatchRng, RngStream},
+};
+
+/// How often the director checks whether it needs to spawn more enemies
+const SPAWN_INTERVAL: f32 = 1.0;
+/// Enemies per living player at the very start of a match
+const BASE_ENEMIES_PER_PLAYER: f32 = 5.0;
+/// How many more enemies per living player the director wants for every minute of the match
+const ENEMIES_PER_PLAYER_PER_MIN: f32 = 4.0;
+/// Hard cap on the number of enemies alive at once, no matter the difficulty
+const MAX_ENEMIES: usize = 400;
+/// The most enemies the director will put out in a single wave
+const MAX_ENEMIES_PER_WAVE: usize = 20;
+/// Enemies show up in a ring around a player, far enough out to be off screen
+const SPAWN_RING: std::ops::Range<f32> = 450.0..550.0;
+
+#[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct EnemySpawnManager {
     pub spawn_style: EnemySpawnStyle,
+    pub spawn_timer: Timer,
+}
+
+impl Default for EnemySpawnManager {
+    fn default() -> Self {
+        Self {
+            spawn_style: EnemySpawnStyle::default(),
+            spawn_timer: Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating),
+        }
+    }
 }
 
 #[derive(Default, Reflect)]
@@ -13,54 +44,125 @@ pub enum EnemySpawnStyle {
     Automatic,
     Manual {
         kind: EnemyKind,
+        pos: Vec2,
         should_fire: bool,
     },
 }
 
+/// The number of enemies the director wants alive, given how long the match has been going
+pub fn target_enemy_count(n_players: usize, elapsed_mins: f32, spawn_rate: f32) -> usize {
+    let per_player = BASE_ENEMIES_PER_PLAYER + ENEMIES_PER_PLAYER_PER_MIN * elapsed_mins;
+    let target = (per_player * n_players as f32 * spawn_rate).floor() as usize;
+    target.min(MAX_ENEMIES)
+}
+
+/// Picks which enemy to spawn, weighted by the spawn rules of everything that's allowed
+/// to show up this far into the match
+pub fn pick_enemy_kind(
+    registry: &EnemyRegistry,
+    elapsed_mins: f32,
+    rng: &mut impl Rng,
+) -> Option<EnemyKind> {
+    let available: Vec<(EnemyKind, f32)> = registry
+        .iter()
+        .filter(|(_, def)| elapsed_mins >= def.spawning.after_mins && def.spawning.weight > 0.0)
+        .map(|(kind, def)| (kind, def.spawning.weight))
+        .collect();
+    let total: f32 = available.iter().map(|(_, w)| w).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.random_range(0.0..total);
+    for (kind, weight) in &available {
+        if roll < *weight {
+            return Some(*kind);
+        }
+        roll -= weight;
+    }
+    available.last().map(|(kind, _)| *kind)
+}
+
 pub fn spawn_enemy_spawn_manager(mut commands: Commands) {
-    commands.insert_resource(EnemySpawnManager {
-        spawn_style: EnemySpawnStyle::Automatic,
-    })
+    commands.insert_resource(EnemySpawnManager::default())
 }
 
-pub fn update_enemy_spawn_manager(
+pub fn update_enemy_spawn_manager<QF: QueryFilter>(
     mut commands: Commands,
     mut manager: ResMut<EnemySpawnManager>,
+    time: Res<Time>,
     game_kinds: Res<CurrentGameKind>,
+    difficulty: Res<DifficultyModifiers>,
+    registry: Res<EnemyRegistry>,
+    elites: Res<EliteTable>,
+    map: Res<CurrentMap>,
+    mut match_rng: ResMut<MatchRng>,
+    q_clock: Query<&MatchClock, QF>,
+    q_players: Query<&Position, (With<Player>, Without<Dead>, QF)>,
+    q_enemies: Query<(), (With<Enemy>, QF)>,
 ) {
-    match manager.spawn_style {
+    let EnemySpawnManager {
+        spawn_style,
+        spawn_timer,
+    } = &mut *manager;
+    match spawn_style {
         EnemySpawnStyle::Automatic => {
-            /*
-            // Derive a difficulty score
-            let n_players = q_players.iter().len() as f32;
-            let time_mins = time.0.elapsed_secs();
-            let n_enemies = q_enemies.iter().len() as f32;
-            let diff_factor = match rules.difficulty {
-                Difficulty::Easy => 5.0,
-                Difficulty::Medium => 10.0,
-                Difficulty::Hard => 15.0,
+            spawn_timer.tick(time.delta());
+            if !spawn_timer.just_finished() {
+                return;
+            }
+            let Some(clock) = q_clock.iter().next() else {
+                return;
             };
+            let player_positions: Vec<Vec2> = q_players.iter().map(|p| p.0).collect();
+            if player_positions.is_empty() {
+                return;
+            }
 
-            let target_diff = n_players * time_mins * diff_factor;
-
-            let curr_diff = n_enemies * n_players * diff_factor;
-
-            let to_spawn = (target_diff - curr_diff).floor() as i32;
+            let target = target_enemy_count(
+                player_positions.len(),
+                clock.elapsed_mins(),
+                difficulty.spawn_rate,
+            );
+            let to_spawn = target
+                .saturating_sub(q_enemies.iter().len())
+                .min(MAX_ENEMIES_PER_WAVE);
 
-            for _i in 0..to_spawn {
-                let x: f32 = (2.0 * (rand::random::<f32>() - 0.5)) * 100.0;
-                let y: f32 = (2.0 * (rand::random::<f32>() - 0.5)) * 100.0;
-                let pos = Vec2::new(x, y);
-                server_spawn_enemy(&mut commands, EnemyKind::default(), pos);
+            let rng = match_rng.stream(RngStream::Spawning);
+            for _ in 0..to_spawn {
+                let center = player_positions[rng.random_range(0..player_positions.len())];
+                let Some(pos) = map.spawn_point_near(center, SPAWN_RING.start, SPAWN_RING.end, rng)
+                else {
+                    continue;
+                };
+                let Some(kind) = pick_enemy_kind(&registry, clock.elapsed_mins(), rng) else {
+                    return;
+                };
+                spawn_enemy(
+                    &mut commands,
+                    &registry,
+                    kind,
+                    game_kinds.0.unwrap(),
+                    pos,
+                    &difficulty,
+                    elites.roll(clock.elapsed_mins(), rng),
+                );
             }
-            */
         }
         EnemySpawnStyle::Manual {
             kind,
-            ref mut should_fire,
+            pos,
+            should_fire,
         } => {
             if *should_fire {
-                spawn_enemy(&mut commands, kind, game_kinds.0.unwrap());
+                spawn_enemy(
+                    &mut commands,
+                    &registry,
+                    *kind,
+                    game_kinds.0.unwrap(),
+                    *pos,
+                    &difficulty,
+                    None,
+                );
                 *should_fire = false;
             }
         }
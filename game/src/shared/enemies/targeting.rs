@@ -0,0 +1,118 @@
+//! How enemies decide which player to go after.
+//!
+//! Enemies look at every player that can be targeted, and score them by distance, with threat
+//! making a player look closer than they are. Every so often, an enemy that's already chasing
+//! somebody takes another look, but it only switches if the new target is better by a margin,
+//! so that enemies don't flip back and forth between two players standing near each other
+use super::*;
+use crate::{
+    shared::{
+        damage::{AppliedDamageMessage, responsible_player},
+        players::{Downed, Spectating, Threat},
+    },
+    utils::CreatedBy,
+};
+
+/// Seconds between an enemy reconsidering its target
+pub const RETARGET_INTERVAL: f32 = 1.0;
+/// How much better (in distance) a new target has to be before an enemy will switch to it
+pub const RETARGET_MARGIN: f32 = 75.0;
+/// Each point of threat makes a player look this much closer
+pub const THREAT_DISTANCE: f32 = 2.0;
+/// Threat gained for each point of damage dealt to an enemy
+pub const THREAT_PER_DAMAGE: f32 = 0.5;
+/// The fraction of threat that wears off every second
+pub const THREAT_DECAY: f32 = 0.25;
+pub const MAX_THREAT: f32 = 200.0;
+
+/// The players that enemies are allowed to go after
+pub type TargetablePlayer = (
+    With<Player>,
+    Without<Enemy>,
+    Without<Dead>,
+    Without<Downed>,
+    Without<Spectating>,
+);
+
+/// Raises the threat of a player. Taunts write this directly, and damage gets turned into it
+#[derive(Message, Debug, Clone, Copy)]
+pub struct RaiseThreatMessage {
+    pub player: Entity,
+    pub amount: f32,
+}
+
+/// Counts down to the next time that an enemy reconsiders its target. It's predicted, so that a
+/// rollback picks targets at the same ticks that the server did
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct RetargetTimer(pub f32);
+
+impl Default for RetargetTimer {
+    fn default() -> Self {
+        Self(RETARGET_INTERVAL)
+    }
+}
+
+/// Lower is a better target
+pub fn target_score(e_pos: Vec2, t_pos: Vec2, threat: f32) -> f32 {
+    e_pos.distance(t_pos) - threat * THREAT_DISTANCE
+}
+
+/// Picks the best scoring candidate, but sticks with the current target unless the best one
+/// beats it by `RETARGET_MARGIN`.
+///
+/// `current` is the current target and its score, if it can still be targeted
+pub fn pick_target(
+    current: Option<(Entity, f32)>,
+    candidates: impl Iterator<Item = (Entity, f32)>,
+) -> Option<Entity> {
+    let best = candidates.min_by(|a, b| a.1.total_cmp(&b.1));
+    match (current, best) {
+        (Some((c_ent, c_score)), Some((b_ent, b_score))) => {
+            if b_score + RETARGET_MARGIN < c_score {
+                Some(b_ent)
+            } else {
+                Some(c_ent)
+            }
+        }
+        (Some((c_ent, _)), None) => Some(c_ent),
+        (None, best) => best.map(|(ent, _)| ent),
+    }
+}
+
+pub fn apply_threat<QF: QueryFilter>(
+    time: Res<Time<Fixed>>,
+    mut messages: MessageReader<RaiseThreatMessage>,
+    mut q_threat: Query<&mut Threat, (With<Player>, QF)>,
+) {
+    for mut threat in &mut q_threat {
+        threat.0 *= (1.0 - THREAT_DECAY * time.delta_secs()).max(0.0);
+    }
+    for m in messages.read() {
+        if let Ok(mut threat) = q_threat.get_mut(m.player) {
+            threat.0 = (threat.0 + m.amount).min(MAX_THREAT);
+        }
+    }
+}
+
+/// Works out which player is responsible for damage landing on an enemy, whether that's the
+/// player themselves or something that one of their weapons made
+pub fn threat_from_damage(
+    mut damage: MessageReader<AppliedDamageMessage>,
+    mut threat: MessageWriter<RaiseThreatMessage>,
+    q_enemy: Query<(), With<Enemy>>,
+    q_player: Query<(), With<Player>>,
+    q_created: Query<&CreatedBy>,
+    q_parent: Query<&ChildOf>,
+) {
+    for hit in damage.read() {
+        if !q_enemy.contains(hit.target) {
+            continue;
+        }
+        if let Some(player) = responsible_player(hit.source, &q_created, &q_parent, &q_player) {
+            threat.write(RaiseThreatMessage {
+                player,
+                amount: hit.amount * THREAT_PER_DAMAGE,
+            });
+        }
+    }
+}
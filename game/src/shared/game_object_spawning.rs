@@ -13,7 +13,16 @@
 //! to spawn the relevant bundle
 use bevy::prelude::*;
 
-use crate::shared::game_kinds::*;
+use crate::shared::{
+    drops::{Pickup, XPGem},
+    enemies::{Enemy, spawner::EnemySpawnManager},
+    game_kinds::*,
+    match_clock::MatchClock,
+    players::Player,
+    projectiles::Projectile,
+    props::Prop,
+    stats::xp::LevelManager,
+};
 
 /// Spawns the entity with the given bundle, ensuring that is happens in the order that is required
 /// so that triggers can function correctly off of the DefaultClientFilter and DefaultServerFilter
@@ -28,3 +37,29 @@ pub fn spawn_game_object(
     commands.entity(entity).insert(bundle);
     entity
 }
+
+/// Clears out everything that was spawned for a game, so that leaving a game and starting
+/// another one doesn't leave stale players or enemies hanging around.
+///
+/// Weapons are children of the players, so they get cleaned up along with them
+pub fn despawn_game_objects(
+    mut commands: Commands,
+    q_objects: Query<
+        Entity,
+        Or<(
+            With<Player>,
+            With<Enemy>,
+            With<Projectile>,
+            With<LevelManager>,
+            With<MatchClock>,
+            With<XPGem>,
+            With<Pickup>,
+            With<Prop>,
+        )>,
+    >,
+) {
+    for ent in &q_objects {
+        commands.entity(ent).despawn();
+    }
+    commands.remove_resource::<EnemySpawnManager>();
+}
@@ -10,23 +10,38 @@ use bevy::prelude::*;
 use lightyear::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::shared::{GameMainChannel, states::AppState};
+use crate::shared::{
+    GameMainChannel,
+    states::AppState,
+    stats::{RawStatsList, xp::add_level_curve},
+};
 
 pub struct SharedGameRulesPlugin;
 
 impl Plugin for SharedGameRulesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Lobby), add_game_rules_resource);
+        // The level curve has to be in place before the level managers get spawned while loading
+        app.add_systems(
+            OnEnter(AppState::Lobby),
+            (add_game_rules_resource, add_level_curve),
+        )
+        .add_systems(OnEnter(AppState::LoadingLevel), add_difficulty_modifiers);
         app.register_message::<ChangeGameRuleMessage<MapKind>>()
             .add_direction(NetworkDirection::ClientToServer);
 
         app.register_message::<ChangeGameRuleMessage<Difficulty>>()
             .add_direction(NetworkDirection::ClientToServer);
+
+        app.register_message::<ChangeGameRuleMessage<XPSharing>>()
+            .add_direction(NetworkDirection::ClientToServer);
     }
 }
 
 pub fn add_game_rules_resource(mut commands: Commands) {
-    commands.insert_resource(GameRules::default())
+    commands.insert_resource(GameRules {
+        seed: rand::random(),
+        ..default()
+    })
 }
 
 /// The central component for how a game gets set up.
@@ -36,11 +51,29 @@ pub fn add_game_rules_resource(mut commands: Commands) {
 /// calls for each of its different elements to be loaded, which the
 /// client will also do.
 /// Once all clients have loaded, the server can move this into
-#[derive(Resource, Default, Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+#[derive(Resource, Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
 #[reflect(Resource)]
 pub struct GameRules {
     pub map_type: MapKind,
     pub difficulty: Difficulty,
+    /// How long (in seconds) the players have to survive in order to win
+    pub match_length: f32,
+    pub xp_sharing: XPSharing,
+    /// Everything random in the match comes from this, so the same seed plays out the same way.
+    /// See `rng` for how it gets used
+    pub seed: u64,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            map_type: MapKind::default(),
+            difficulty: Difficulty::default(),
+            match_length: 20.0 * 60.0,
+            xp_sharing: XPSharing::default(),
+            seed: 0,
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Reflect)]
@@ -70,6 +103,87 @@ impl GameRuleField for Difficulty {
     }
 }
 
+/// Whether the party levels up together, or each player is on their own
+#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Reflect)]
+pub enum XPSharing {
+    /// There's one level for the whole party, and any XP that anybody picks up goes towards it
+    #[default]
+    Shared,
+    /// Every player has their own level, and only gets the XP that they pick up themselves
+    PerPlayer,
+}
+impl GameRuleField for XPSharing {
+    fn set_field(&self, rules: &mut GameRules) {
+        rules.xp_sharing = *self
+    }
+}
+
+unsafe impl Send for XPSharing {}
+
+/// How much each difficulty changes the game, as read from `assets/game_rules/difficulty.ron`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+pub struct DifficultyTable {
+    pub easy: DifficultyModifiers,
+    pub normal: DifficultyModifiers,
+    pub hard: DifficultyModifiers,
+}
+
+impl DifficultyTable {
+    pub const PATH: &'static str = "assets/game_rules/difficulty.ron";
+
+    pub fn import() -> Self {
+        crate::utils::read_ron::<DifficultyTable>(Self::PATH.into())
+    }
+
+    pub fn get(&self, difficulty: Difficulty) -> DifficultyModifiers {
+        match difficulty {
+            Difficulty::Easy => self.easy,
+            Difficulty::Normal => self.normal,
+            Difficulty::Hard => self.hard,
+        }
+    }
+}
+
+/// The multipliers for the difficulty that the current game is being played on.
+///
+/// These are inserted as a resource while the level loads, so that anything spawning
+/// enemies can scale them without having to know about the table
+#[derive(Resource, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct DifficultyModifiers {
+    pub enemy_health: f32,
+    pub enemy_damage: f32,
+    pub enemy_speed: f32,
+    /// Scales how many enemies the spawn director wants on the field at once
+    pub spawn_rate: f32,
+    /// Scales how much XP enemies drop
+    pub xp_yield: f32,
+}
+
+impl Default for DifficultyModifiers {
+    fn default() -> Self {
+        Self {
+            enemy_health: 1.0,
+            enemy_damage: 1.0,
+            enemy_speed: 1.0,
+            spawn_rate: 1.0,
+            xp_yield: 1.0,
+        }
+    }
+}
+
+impl DifficultyModifiers {
+    /// Scales the stats that an enemy is about to be spawned with
+    pub fn apply_to_enemy_stats(&self, stats: &mut RawStatsList) {
+        stats.scale_enemy_stats(self.enemy_health, self.enemy_damage, self.enemy_speed);
+    }
+}
+
+pub fn add_difficulty_modifiers(mut commands: Commands, rules: Res<GameRules>) {
+    let modifiers = DifficultyTable::import().get(rules.difficulty);
+    commands.insert_resource(modifiers);
+}
+
 /*
 trait ChangesGameRules: lightyear::prelude::Message {
     fn apply(&self, rules: &mut GameRules);
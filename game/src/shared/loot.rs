@@ -0,0 +1,170 @@
+//! What gets dropped when something dies, breaks or gets opened, as read from
+//! `assets/game_rules/loot_tables.ron`.
+//!
+//! Every table is a list of weighted entries, and `Luck` bends those weights: an entry's weight
+//! is multiplied by the player's luck raised to that entry's `luck_scaling`. So good stuff
+//! (positive scaling) gets more likely for a lucky player, and `Nothing` (negative scaling)
+//! gets less likely. With the default luck of 1, the weights are exactly as written.
+//!
+//! Rolls take their RNG as an argument, and in a match that's the `Loot` stream of `MatchRng`,
+//! so the same match seed always gives the same drops
+use bevy::{ecs::query::QueryFilter, platform::collections::HashMap, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::shared::{
+    drops::{PickupKind, spawn_pickup},
+    game_kinds::CurrentGameKind,
+    players::Player,
+    props::PropKind,
+    rng::{MatchRng, RngStream},
+    stats::components::Luck,
+};
+
+/// How far apart the things that come out of one roll get spread, so they don't stack up
+const DROP_SPREAD: f32 = 20.0;
+
+pub struct SharedLootPlugin;
+
+impl Plugin for SharedLootPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ChestOpenedMessage>()
+            .add_systems(Startup, load_loot_tables);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum LootDrop {
+    Nothing,
+    Pickup(PickupKind),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct LootEntry {
+    pub drop: LootDrop,
+    pub weight: f32,
+    /// How much `Luck` changes this entry's weight. 0 means not at all
+    #[serde(default)]
+    pub luck_scaling: f32,
+}
+
+impl LootEntry {
+    pub fn weight_with(&self, luck: f32) -> f32 {
+        (self.weight * luck.max(0.01).powf(self.luck_scaling)).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct LootTable {
+    /// How many times the table gets rolled each time something drops from it
+    pub rolls: u32,
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    pub fn roll_once(&self, luck: f32, rng: &mut impl Rng) -> LootDrop {
+        let total: f32 = self.entries.iter().map(|e| e.weight_with(luck)).sum();
+        if total <= 0.0 {
+            return LootDrop::Nothing;
+        }
+        let mut pick = rng.random_range(0.0..total);
+        for entry in &self.entries {
+            pick -= entry.weight_with(luck);
+            if pick < 0.0 {
+                return entry.drop;
+            }
+        }
+        self.entries.last().map_or(LootDrop::Nothing, |e| e.drop)
+    }
+
+    /// Everything that actually drops, leaving out the rolls that came up with nothing
+    pub fn roll(&self, luck: f32, rng: &mut impl Rng) -> Vec<PickupKind> {
+        (0..self.rolls)
+            .filter_map(|_| match self.roll_once(luck, rng) {
+                LootDrop::Nothing => None,
+                LootDrop::Pickup(kind) => Some(kind),
+            })
+            .collect()
+    }
+}
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct LootTables {
+    /// Rolled for every enemy, on top of its XP
+    pub enemy: LootTable,
+    /// Rolled for elites, instead of `enemy`
+    pub elite: LootTable,
+    pub props: HashMap<PropKind, LootTable>,
+    pub chest: LootTable,
+}
+
+impl LootTables {
+    pub const PATH: &'static str = "assets/game_rules/loot_tables.ron";
+
+    pub fn import() -> Self {
+        crate::utils::read_ron::<LootTables>(Self::PATH.into())
+    }
+
+    pub fn prop(&self, kind: PropKind) -> Option<&LootTable> {
+        self.props.get(&kind)
+    }
+}
+
+pub fn load_loot_tables(mut commands: Commands) {
+    commands.insert_resource(LootTables::import());
+}
+
+/// The luck of whoever is behind a drop. Drops that no player caused get the default luck
+pub fn luck_of<F: QueryFilter>(player: Option<Entity>, q_luck: &Query<&Luck, F>) -> f32 {
+    player.and_then(|p| q_luck.get(p).ok()).map_or(1.0, |l| l.0)
+}
+
+/// Rolls `table` and puts whatever comes out around `pos`
+pub fn drop_loot(
+    commands: &mut Commands,
+    gk: &CurrentGameKind,
+    table: &LootTable,
+    luck: f32,
+    rng: &mut impl Rng,
+    pos: Vec2,
+) {
+    let drops = table.roll(luck, rng);
+    let n = drops.len();
+    for (i, kind) in drops.into_iter().enumerate() {
+        let offset = if n > 1 {
+            Vec2::from_angle(std::f32::consts::TAU * i as f32 / n as f32) * DROP_SPREAD
+        } else {
+            Vec2::ZERO
+        };
+        spawn_pickup(commands, gk.0.unwrap(), pos + offset, kind);
+    }
+}
+
+/// Written when a player picks up a chest, so the contents can be rolled with their luck
+#[derive(Message, Debug, Clone, Copy)]
+pub struct ChestOpenedMessage {
+    pub opener: Entity,
+    pub pos: Vec2,
+}
+
+pub fn open_chests<QF: QueryFilter>(
+    mut commands: Commands,
+    mut opened: MessageReader<ChestOpenedMessage>,
+    gk: Res<CurrentGameKind>,
+    tables: Res<LootTables>,
+    mut rng: ResMut<MatchRng>,
+    q_luck: Query<&Luck, (With<Player>, QF)>,
+) {
+    for chest in opened.read() {
+        let luck = luck_of(Some(chest.opener), &q_luck);
+        drop_loot(
+            &mut commands,
+            &gk,
+            &tables.chest,
+            luck,
+            rng.stream(RngStream::Loot),
+            chest.pos,
+        );
+    }
+}
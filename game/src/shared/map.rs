@@ -0,0 +1,237 @@
+//! The layout of the map that a match is played on.
+//!
+//! Every `MapKind` has a definition in `assets/maps`, with the bounds of the world, the static
+//! obstacles in it, the areas that enemies are allowed to spawn in, and where the players start.
+//! The server and the clients each load it while the level loads. The obstacles never move, so
+//! rather than replicating them, everybody spawns their own copy
+use avian2d::prelude::*;
+use bevy::{ecs::query::QueryFilter, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::shared::{
+    colliders::{ColliderTypes, CommonColliderBundle},
+    enemies::{Enemy, navigation::NavObstacle},
+    game_rules::{GameRules, MapKind},
+    players::Player,
+    props::PropKind,
+    states::AppState,
+};
+
+/// How many places the spawner tries before it gives up on spawning something this wave
+pub const SPAWN_ATTEMPTS: usize = 8;
+
+pub struct SharedMapPlugin;
+
+impl Plugin for SharedMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::LoadingLevel), load_map);
+    }
+}
+
+impl MapKind {
+    pub fn definition_path(&self) -> String {
+        let file = match self {
+            Self::TheGreens => "the_greens",
+        };
+        format!("assets/maps/{}.ron", file)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
+pub struct MapDefinition {
+    pub name: String,
+    /// Nothing gets to leave this
+    pub bounds: Rect,
+    pub obstacles: Vec<MapObstacle>,
+    /// Enemies only spawn inside of these. An empty list means anywhere in bounds
+    pub spawn_zones: Vec<SpawnZone>,
+    pub player_start: PlayerStart,
+    /// Keeps drawing the background past the bounds, so the edge of the world isn't a hard line
+    #[serde(default)]
+    pub endless_background: bool,
+    /// The props that are already there when the match starts
+    #[serde(default)]
+    pub props: Vec<MapProp>,
+    /// Leaving this out means no more props get put down once the match is going
+    #[serde(default)]
+    pub prop_spawning: Option<PropSpawnRules>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+pub struct MapProp {
+    pub kind: PropKind,
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
+pub struct PropSpawnRules {
+    /// Seconds between new props
+    pub interval: f32,
+    /// No more get put down while there are this many around
+    pub max_props: usize,
+    pub kinds: Vec<PropKind>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+pub struct MapObstacle {
+    pub center: Vec2,
+    pub shape: ObstacleShape,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+pub enum ObstacleShape {
+    Rectangle { width: f32, height: f32 },
+    Circle { radius: f32 },
+}
+
+impl MapObstacle {
+    pub fn contains(&self, pos: Vec2) -> bool {
+        let local = pos - self.center;
+        match self.shape {
+            ObstacleShape::Rectangle { width, height } => {
+                local.x.abs() <= width / 2.0 && local.y.abs() <= height / 2.0
+            }
+            ObstacleShape::Circle { radius } => local.length() <= radius,
+        }
+    }
+
+    pub fn collider(&self) -> Collider {
+        match self.shape {
+            ObstacleShape::Rectangle { width, height } => Collider::rectangle(width, height),
+            ObstacleShape::Circle { radius } => Collider::circle(radius),
+        }
+    }
+}
+
+/// A polygon, with its points going around the edge in order
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
+pub struct SpawnZone {
+    pub points: Vec<Vec2>,
+}
+
+impl SpawnZone {
+    pub fn contains(&self, pos: Vec2) -> bool {
+        // Counts how many edges a ray going right from `pos` crosses
+        let mut inside = false;
+        let n = self.points.len();
+        for i in 0..n {
+            let (a, b) = (self.points[i], self.points[(i + 1) % n]);
+            if (a.y > pos.y) != (b.y > pos.y) {
+                let cross_x = a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if pos.x < cross_x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+pub struct PlayerStart {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl PlayerStart {
+    pub fn random_point(&self, rng: &mut impl Rng) -> Vec2 {
+        let angle = rng.random_range(0.0..std::f32::consts::TAU);
+        let dist = self.radius * rng.random_range(0.0f32..1.0).sqrt();
+        self.center + Vec2::from_angle(angle) * dist
+    }
+}
+
+impl MapDefinition {
+    pub fn import(kind: MapKind) -> Self {
+        crate::utils::read_ron::<MapDefinition>(kind.definition_path())
+    }
+
+    pub fn in_obstacle(&self, pos: Vec2) -> bool {
+        self.obstacles.iter().any(|o| o.contains(pos))
+    }
+
+    /// Whether an enemy is allowed to show up at `pos`
+    pub fn is_valid_spawn(&self, pos: Vec2) -> bool {
+        self.bounds.contains(pos)
+            && !self.in_obstacle(pos)
+            && (self.spawn_zones.is_empty() || self.spawn_zones.iter().any(|z| z.contains(pos)))
+    }
+
+    /// Looks for somewhere that an enemy can spawn, between `min_dist` and `max_dist` away
+    /// from `center`. This gives up after a few tries, rather than looping forever on a map
+    /// where there's nowhere valid nearby
+    pub fn spawn_point_near(
+        &self,
+        center: Vec2,
+        min_dist: f32,
+        max_dist: f32,
+        rng: &mut impl Rng,
+    ) -> Option<Vec2> {
+        (0..SPAWN_ATTEMPTS)
+            .map(|_| {
+                let angle = rng.random_range(0.0..std::f32::consts::TAU);
+                center + Vec2::from_angle(angle) * rng.random_range(min_dist..=max_dist)
+            })
+            .find(|pos| self.is_valid_spawn(*pos))
+    }
+
+    pub fn clamp(&self, pos: Vec2) -> Vec2 {
+        pos.clamp(self.bounds.min, self.bounds.max)
+    }
+}
+
+/// The map for the match that's loading or being played
+#[derive(Resource, Debug, Clone, Deref, Reflect)]
+#[reflect(Resource)]
+pub struct CurrentMap(pub MapDefinition);
+
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[require(NavObstacle)]
+pub struct Obstacle;
+
+impl From<&MapObstacle> for CommonColliderBundle {
+    fn from(value: &MapObstacle) -> Self {
+        Self::new(
+            RigidBody::Static,
+            value.collider(),
+            1.0,
+            [ColliderTypes::Obstacle].into(),
+            [ColliderTypes::Player, ColliderTypes::Enemy].into(),
+        )
+    }
+}
+
+pub fn load_map(mut commands: Commands, rules: Res<GameRules>) {
+    let map = MapDefinition::import(rules.map_type);
+    for obstacle in &map.obstacles {
+        commands.spawn((
+            Obstacle,
+            Name::from("Obstacle"),
+            Position(obstacle.center),
+            CommonColliderBundle::from(obstacle),
+            DespawnOnExit(AppState::InGame),
+        ));
+    }
+    commands.insert_resource(CurrentMap(map));
+}
+
+/// Keeps players and enemies inside the map, and stops them pushing against the edge
+pub fn clamp_to_map_bounds<QF: QueryFilter>(
+    map: Res<CurrentMap>,
+    mut q_units: Query<(&mut Position, &mut LinearVelocity), (Or<(With<Player>, With<Enemy>)>, QF)>,
+) {
+    for (mut pos, mut lv) in &mut q_units {
+        let clamped = map.clamp(pos.0);
+        if clamped == pos.0 {
+            continue;
+        }
+        if clamped.x != pos.0.x {
+            lv.x = 0.0;
+        }
+        if clamped.y != pos.0.y {
+            lv.y = 0.0;
+        }
+        pos.0 = clamped;
+    }
+}
@@ -0,0 +1,129 @@
+//! The clock for how long the current match has been going on.
+//!
+//! The server owns the clock and replicates it out to the clients, so that everybody agrees on
+//! what time it is for things like spawning and the survival win condition. In single player,
+//! the clock only ticks while `InGameState::InGame`, so pausing stops it
+use bevy::{ecs::query::QueryFilter, prelude::*};
+use lightyear::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::shared::{
+    damage::Dead,
+    game_kinds::{CurrentGameKind, MultiPlayerComponentOptions},
+    game_object_spawning::spawn_game_object,
+    game_rules::GameRules,
+    players::Player,
+    states::{AppState, InGameState},
+};
+
+pub struct MatchClockProtocolPlugin;
+
+impl Plugin for MatchClockProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_component::<MatchClock>();
+        app.register_message::<MatchEndedMessage>()
+            .add_direction(NetworkDirection::ServerToClient);
+        app.add_message::<MatchEndedMessage>();
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct MatchClock {
+    /// Seconds since the match started, not counting time spent paused
+    pub elapsed: f32,
+}
+
+impl MatchClock {
+    pub fn elapsed_mins(&self) -> f32 {
+        self.elapsed / 60.0
+    }
+}
+
+/// Nobody predicts the clock, the clients just take what the server says
+impl From<MatchClock> for MultiPlayerComponentOptions {
+    fn from(_value: MatchClock) -> Self {
+        Self {
+            pred: false,
+            interp: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum MatchOutcome {
+    /// Somebody made it to the end of the match
+    Victory,
+    /// Everybody died before time ran out
+    Defeat,
+}
+
+/// Sent by the server when the match is over. In single player, this gets written locally instead
+#[derive(Message, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MatchEndedMessage {
+    pub outcome: MatchOutcome,
+}
+
+/// How the last match went, for the post game to show once everything from it is gone
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct LastMatchOutcome(pub MatchOutcome);
+
+pub fn add_match_clock(mut commands: Commands, gk: Res<CurrentGameKind>) {
+    let clock = MatchClock::default();
+    spawn_game_object(
+        &mut commands,
+        gk.0.unwrap(),
+        MultiPlayerComponentOptions::from(clock),
+        (clock, Name::from("Match Clock")),
+    );
+}
+
+pub fn tick_match_clock<QF: QueryFilter>(
+    time: Res<Time<Fixed>>,
+    mut q_clock: Query<&mut MatchClock, QF>,
+) {
+    for mut clock in &mut q_clock {
+        clock.elapsed += time.delta_secs();
+    }
+}
+
+/// Ends the match once the clock runs past the match length, or once every player is dead.
+///
+/// The returned outcome is piped into whatever needs to let the rest of the app know
+pub fn check_match_end<ClockQF: QueryFilter, PlayerQF: QueryFilter>(
+    rules: Res<GameRules>,
+    q_clock: Query<&MatchClock, ClockQF>,
+    q_players: Query<Has<Dead>, (With<Player>, PlayerQF)>,
+) -> Option<MatchOutcome> {
+    let clock = q_clock.iter().next()?;
+    if !q_players.is_empty() && q_players.iter().all(|dead| dead) {
+        Some(MatchOutcome::Defeat)
+    } else if clock.elapsed >= rules.match_length {
+        Some(MatchOutcome::Victory)
+    } else {
+        None
+    }
+}
+
+pub fn write_match_ended(
+    outcome: In<Option<MatchOutcome>>,
+    mut messages: MessageWriter<MatchEndedMessage>,
+) {
+    if let Some(outcome) = outcome.0 {
+        messages.write(MatchEndedMessage { outcome });
+    }
+}
+
+/// Moves the app on to the post game when a match ends, regardless of where the news came from
+pub fn move_to_post_game(
+    mut commands: Commands,
+    mut messages: MessageReader<MatchEndedMessage>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut game_state: ResMut<NextState<InGameState>>,
+) {
+    if let Some(m) = messages.read().last() {
+        info!("Match over: {:?}", m.outcome);
+        commands.insert_resource(LastMatchOutcome(m.outcome));
+        app_state.set(AppState::PostGame);
+        game_state.set(InGameState::OutOfGame);
+    }
+}
@@ -0,0 +1,288 @@
+//! What the consumable pickups actually do once a player walks over them.
+//!
+//! Every kind of pickup is its own `CollisionEffect`, which `spawn_pickup` hands to the pickup
+//! along with the `Pickup` itself. Like the XP gems, the effects are never replicated, so only
+//! the server (or the single player client) gets to decide that something was picked up.
+//! Some of them only do something for the player that took them (heals, gold), and the rest
+//! affect the whole world (magnets, bombs, freezes)
+use avian2d::prelude::*;
+use bevy::{ecs::query::QueryFilter, prelude::*};
+use lightyear::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::shared::{
+    colliders::*,
+    combat::CombatSystemSet,
+    damage::{DamageBuffer, DamageInstance, Dead},
+    drops::{CollectXP, Pickup, PickupCollectedMessage, PickupKind, XPGem},
+    enemies::Enemy,
+    loot::ChestOpenedMessage,
+    players::{Gold, VIEW_EXTENT},
+    states::InGameState,
+    stats::components::Health,
+};
+
+/// Bombs hit every enemy within this of the player that picked them up, which is as far as the
+/// camera can see
+pub const BOMB_EXTENT: Vec2 = VIEW_EXTENT;
+/// How fast gems fly towards whoever picked up a magnet
+pub const VACUUM_SPEED: f32 = 900.0;
+/// Vacuumed gems get collected once they're this close, rather than waiting for a collision
+pub const VACUUM_COLLECT_DISTANCE: f32 = 16.0;
+
+pub struct PickupsProtocolPlugin;
+
+impl Plugin for PickupsProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_component::<Frozen>().add_prediction();
+    }
+}
+
+pub struct SharedPickupsPlugin;
+
+impl Plugin for SharedPickupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedPostUpdate,
+            (
+                collision_start_effect_system::<Heal>,
+                collision_start_effect_system::<Magnet>,
+                collision_start_effect_system::<Bomb>,
+                collision_start_effect_system::<Freeze>,
+                collision_start_effect_system::<CollectGold>,
+                collision_start_effect_system::<OpenChest>,
+            )
+                .after(PhysicsSystems::Last)
+                .in_set(CombatSystemSet::PostPhysicsSet)
+                .run_if(in_state(InGameState::InGame)),
+        );
+    }
+}
+
+/// Gives the pickup's effect to the pickup, so that it's ready to be collected
+pub fn insert_pickup_effect(commands: &mut EntityCommands, kind: PickupKind) {
+    let to = [ColliderTypes::Player].into();
+    match kind {
+        PickupKind::Heal { amount } => {
+            commands.insert(AppliesCollisionEffect::new(to, Heal { amount }))
+        }
+        PickupKind::Magnet => commands.insert(AppliesCollisionEffect::new(to, Magnet)),
+        PickupKind::Bomb { damage } => {
+            commands.insert(AppliesCollisionEffect::new(to, Bomb { damage }))
+        }
+        PickupKind::Freeze { duration } => {
+            commands.insert(AppliesCollisionEffect::new(to, Freeze { duration }))
+        }
+        PickupKind::Gold { amount } => {
+            commands.insert(AppliesCollisionEffect::new(to, CollectGold { amount }))
+        }
+        PickupKind::Chest => commands.insert(AppliesCollisionEffect::new(to, OpenChest)),
+    };
+}
+
+/// Takes the pickup for `to` if nobody beat them to it, the same way `CollectXP` does for gems.
+/// Returns whether `to` was the one that got it
+fn claim_pickup(world: &mut World, to: Entity, from: Entity) -> bool {
+    let Some(pickup) = world.get::<Pickup>(from).copied() else {
+        return false;
+    };
+    world.despawn(from);
+    world.write_message(PickupCollectedMessage {
+        collector: to,
+        kind: pickup.kind,
+    });
+    true
+}
+
+/// `to` is the player, and `from` is the pickup
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Heal {
+    pub amount: f32,
+}
+
+impl CollisionEffect for Heal {
+    fn apply_to(&self, coms: &mut Commands, to: Entity, from: Entity) {
+        let amount = self.amount;
+        coms.queue(move |world: &mut World| {
+            if !claim_pickup(world, to, from) {
+                return;
+            }
+            if let Some(mut health) = world.get_mut::<Health>(to) {
+                health.current = (health.current + amount).min(health.max);
+            }
+        });
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct CollectGold {
+    pub amount: u32,
+}
+
+impl CollisionEffect for CollectGold {
+    fn apply_to(&self, coms: &mut Commands, to: Entity, from: Entity) {
+        let amount = self.amount;
+        coms.queue(move |world: &mut World| {
+            if !claim_pickup(world, to, from) {
+                return;
+            }
+            if let Some(mut gold) = world.get_mut::<Gold>(to) {
+                gold.0 += amount;
+            }
+        });
+    }
+}
+
+/// What's inside gets rolled with the luck of whoever opened it
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct OpenChest;
+
+impl CollisionEffect for OpenChest {
+    fn apply_to(&self, coms: &mut Commands, to: Entity, from: Entity) {
+        coms.queue(move |world: &mut World| {
+            let Some(pos) = world.get::<Position>(from).map(|p| p.0) else {
+                return;
+            };
+            if !claim_pickup(world, to, from) {
+                return;
+            }
+            world.write_message(ChestOpenedMessage { opener: to, pos });
+        });
+    }
+}
+
+/// Pulls every XP gem on the map towards the player that picked it up
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Magnet;
+
+impl CollisionEffect for Magnet {
+    fn apply_to(&self, coms: &mut Commands, to: Entity, from: Entity) {
+        coms.queue(move |world: &mut World| {
+            if !claim_pickup(world, to, from) {
+                return;
+            }
+            let gems: Vec<Entity> = world
+                .query_filtered::<Entity, With<XPGem>>()
+                .iter(world)
+                .collect();
+            for gem in gems {
+                world.entity_mut(gem).insert(Vacuumed { to });
+            }
+        });
+    }
+}
+
+/// A gem on its way to a player, after a magnet was picked up
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct Vacuumed {
+    pub to: Entity,
+}
+
+/// Hurts every enemy on screen
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Bomb {
+    pub damage: f32,
+}
+
+impl CollisionEffect for Bomb {
+    fn apply_to(&self, coms: &mut Commands, to: Entity, from: Entity) {
+        let damage = self.damage;
+        coms.queue(move |world: &mut World| {
+            if !claim_pickup(world, to, from) {
+                return;
+            }
+            let Some(center) = world.get::<Position>(to).map(|p| p.0) else {
+                return;
+            };
+            let area = Rect::from_center_half_size(center, BOMB_EXTENT);
+            let mut q_enemies = world
+                .query_filtered::<(&Position, &mut DamageBuffer), (With<Enemy>, Without<Dead>)>();
+            for (pos, mut buffer) in q_enemies.iter_mut(world) {
+                if area.contains(pos.0) {
+                    buffer.push(DamageInstance {
+                        damage_source: to,
+                        amount: damage,
+                        crit: false,
+                    });
+                }
+            }
+        });
+    }
+}
+
+/// Stops every enemy in its tracks for a while
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Freeze {
+    pub duration: f32,
+}
+
+impl CollisionEffect for Freeze {
+    fn apply_to(&self, coms: &mut Commands, to: Entity, from: Entity) {
+        let duration = self.duration;
+        coms.queue(move |world: &mut World| {
+            if !claim_pickup(world, to, from) {
+                return;
+            }
+            let enemies: Vec<Entity> = world
+                .query_filtered::<Entity, (With<Enemy>, Without<Dead>)>()
+                .iter(world)
+                .collect();
+            for enemy in enemies {
+                // Picking up another freeze while one is going only ever makes it last longer
+                let remaining = world
+                    .get::<Frozen>(enemy)
+                    .map_or(duration, |f| f.remaining.max(duration));
+                world.entity_mut(enemy).insert(Frozen { remaining });
+            }
+        });
+    }
+}
+
+/// A frozen enemy doesn't move, attack (not even by touching someone), or carry on with its
+/// behaviour until this runs out.
+///
+/// This is predicted, so that a client's enemies stop at the same time as the server's
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct Frozen {
+    pub remaining: f32,
+}
+
+/// Pulls vacuumed gems in, and hands over their XP once they get there
+pub fn vacuum_gems<QF: QueryFilter>(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut q_gems: Query<(Entity, &Vacuumed, &mut Position), (With<XPGem>, QF)>,
+    q_players: Query<&Position, (Without<XPGem>, Without<Dead>)>,
+) {
+    for (ent, vacuumed, mut pos) in &mut q_gems {
+        let Ok(p_pos) = q_players.get(vacuumed.to) else {
+            // Whoever it was going to is gone, so it stays where it is
+            commands.entity(ent).remove::<Vacuumed>();
+            continue;
+        };
+        let to_player = p_pos.0 - pos.0;
+        let step = VACUUM_SPEED * time.delta_secs();
+        if to_player.length() <= VACUUM_COLLECT_DISTANCE + step {
+            // The gem could still get collected the usual way on this same tick
+            CollectXP.apply_to(&mut commands, vacuumed.to, ent);
+        } else {
+            pos.0 += to_player.normalize() * step;
+        }
+    }
+}
+
+/// Keeps frozen enemies where they are, and thaws them out once the freeze runs out. This has
+/// to run after everything else that sets an enemy's velocity
+pub fn hold_frozen_enemies<QF: QueryFilter>(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut q_frozen: Query<(Entity, &mut Frozen, &mut LinearVelocity), QF>,
+) {
+    for (ent, mut frozen, mut lv) in &mut q_frozen {
+        lv.0 = Vec2::ZERO;
+        frozen.remaining -= time.delta_secs();
+        if frozen.remaining <= 0.0 {
+            commands.entity(ent).remove::<Frozen>();
+        }
+    }
+}
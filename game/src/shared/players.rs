@@ -4,6 +4,7 @@ use crate::{
         game_kinds::{MultiPlayerComponentOptions, SinglePlayer},
         inputs::Movement,
         stats::components::MovementSpeed,
+        weapons::WeaponKind,
     },
     utils::AssetFolder,
 };
@@ -13,16 +14,38 @@ use bevy_enhanced_input::prelude::*;
 use lightyear::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Half the size of what the camera shows around a player, in world units
+pub const VIEW_EXTENT: Vec2 = Vec2::new(960.0, 540.0);
+
 /// The component that describes a player.
 /// This holds a record of the peer id so that,
 /// if a client disconnects, we can still maintain
 /// state of the character while we wait for that person
 /// to come back
 #[derive(Component, Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Reflect)]
+#[require(Threat, Gold)]
 pub struct Player {
     pub client: PeerId,
 }
 
+/// The player is down and waiting to be revived. Enemies leave them alone in the meantime
+#[derive(Component, Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Reflect)]
+pub struct Downed;
+
+/// The player is only watching, and isn't a part of the game
+#[derive(Component, Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Reflect)]
+pub struct Spectating;
+
+/// How much the enemies want to go after this player, on top of how close they are.
+///
+/// Dealing damage and taunting raise it, and it wears off over time
+#[derive(Component, Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Reflect)]
+pub struct Threat(pub f32);
+
+/// How much gold the player has picked up this match
+#[derive(Component, Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Reflect)]
+pub struct Gold(pub u32);
+
 impl From<Player> for CommonColliderBundle {
     fn from(value: Player) -> Self {
         Self::new(
@@ -32,8 +55,11 @@ impl From<Player> for CommonColliderBundle {
             [ColliderTypes::Player].into(),
             [
                 ColliderTypes::Enemy,
+                ColliderTypes::EnemyProjectile,
                 ColliderTypes::StaticPickup,
                 ColliderTypes::RemotePickup,
+                ColliderTypes::Obstacle,
+                ColliderTypes::Prop,
             ]
             .into(),
         )
@@ -49,12 +75,20 @@ impl From<Player> for MultiPlayerComponentOptions {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum CharacterKind {
     #[default]
     Dewey,
 }
 
+/// What the local player starts a single player match as
+#[derive(Resource, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Resource)]
+pub struct PlayerLoadout {
+    pub character: CharacterKind,
+    pub weapon: WeaponKind,
+}
+
 impl From<CharacterKind> for AssetFolder {
     fn from(value: CharacterKind) -> Self {
         let s = match value {
@@ -68,6 +102,10 @@ pub struct PlayerProtocolPlugin;
 impl Plugin for PlayerProtocolPlugin {
     fn build(&self, app: &mut App) {
         app.register_component::<Player>();
+        app.register_component::<Downed>().add_prediction();
+        app.register_component::<Spectating>().add_prediction();
+        app.register_component::<Threat>().add_prediction();
+        app.register_component::<Gold>().add_prediction();
     }
 }
 
@@ -22,6 +22,15 @@ impl Plugin for ProjectileProtocolPlugin {
 #[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Projectile {
     pub movement: ProjectileMovement,
+    pub faction: ProjectileFaction,
+}
+
+/// Who fired the projectile, which decides what it's able to hit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum ProjectileFaction {
+    #[default]
+    Player,
+    Enemy,
 }
 
 impl MapEntities for Projectile {
@@ -42,12 +51,22 @@ impl MapEntities for Projectile {
 
 impl From<Projectile> for CommonColliderBundle {
     fn from(value: Projectile) -> Self {
+        let (membership, filter): (ColliderTypes, LayerMask) = match value.faction {
+            ProjectileFaction::Player => (
+                ColliderTypes::PlayerProjectile,
+                [ColliderTypes::Enemy, ColliderTypes::Prop].into(),
+            ),
+            ProjectileFaction::Enemy => (
+                ColliderTypes::EnemyProjectile,
+                [ColliderTypes::Player].into(),
+            ),
+        };
         Self::new(
             RigidBody::Kinematic,
             Collider::rectangle(20.0, 20.0),
             1.0,
-            [ColliderTypes::PlayerProjectile].into(),
-            [ColliderTypes::Enemy].into(),
+            [membership].into(),
+            filter,
         )
     }
 }
@@ -0,0 +1,214 @@
+//! Things in the world, like coolers and tables, that players can break open for pickups.
+//!
+//! Some are placed by the map definition, and the rest get put down over the course of the
+//! match. Either way, only the server (or the single player client) spawns them and decides
+//! what they drop, and everybody else gets them through replication
+use avian2d::prelude::*;
+use bevy::{ecs::query::QueryFilter, prelude::*};
+use lightyear::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    shared::{
+        colliders::*,
+        damage::{Dead, KilledBy, responsible_player},
+        enemies::navigation::NavObstacle,
+        game_kinds::{CurrentGameKind, GameKinds, MultiPlayerComponentOptions},
+        game_object_spawning::spawn_game_object,
+        loot::{LootTables, drop_loot, luck_of},
+        map::CurrentMap,
+        players::Player,
+        rng::{MatchRng, RngStream},
+        stats::components::{Health, Luck},
+    },
+    utils::CreatedBy,
+};
+
+/// New props get put down somewhere between these distances from a player, so they show up
+/// just off screen
+const PROP_SPAWN_RING: std::ops::Range<f32> = 500.0..900.0;
+
+pub struct PropsProtocolPlugin;
+
+impl Plugin for PropsProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_component::<Prop>().add_prediction();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Reflect)]
+pub enum PropKind {
+    Cooler,
+    Table,
+}
+
+impl PropKind {
+    pub fn size(&self) -> Vec2 {
+        match self {
+            Self::Cooler => Vec2::new(40.0, 30.0),
+            Self::Table => Vec2::new(90.0, 50.0),
+        }
+    }
+
+    pub fn health(&self) -> f32 {
+        match self {
+            Self::Cooler => 20.0,
+            Self::Table => 45.0,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+#[require(NavObstacle)]
+pub struct Prop {
+    pub kind: PropKind,
+}
+
+impl From<Prop> for CommonColliderBundle {
+    fn from(value: Prop) -> Self {
+        let size = value.kind.size();
+        Self::new(
+            RigidBody::Static,
+            Collider::rectangle(size.x, size.y),
+            1.0,
+            [ColliderTypes::Prop].into(),
+            [
+                ColliderTypes::Player,
+                ColliderTypes::Enemy,
+                ColliderTypes::PlayerProjectile,
+            ]
+            .into(),
+        )
+    }
+}
+
+impl From<Prop> for MultiPlayerComponentOptions {
+    fn from(_value: Prop) -> Self {
+        Self {
+            pred: true,
+            interp: false,
+        }
+    }
+}
+
+/// Puts down new props over the course of the match, if the map asks for it
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct PropSpawner {
+    pub timer: Timer,
+}
+
+pub fn spawn_prop(
+    commands: &mut Commands,
+    game_kind: GameKinds,
+    kind: PropKind,
+    pos: Vec2,
+) -> Entity {
+    let prop = Prop { kind };
+    spawn_game_object(
+        commands,
+        game_kind,
+        MultiPlayerComponentOptions::from(prop),
+        (
+            prop,
+            Position(pos),
+            Health {
+                max: kind.health(),
+                current: kind.health(),
+            },
+        ),
+    )
+}
+
+/// Places the props from the map definition, and gets the spawner going
+pub fn spawn_map_props(mut commands: Commands, map: Res<CurrentMap>, gk: Res<CurrentGameKind>) {
+    for prop in &map.props {
+        spawn_prop(&mut commands, gk.0.unwrap(), prop.kind, prop.position);
+    }
+    if let Some(rules) = &map.prop_spawning {
+        commands.insert_resource(PropSpawner {
+            timer: Timer::from_seconds(rules.interval, TimerMode::Repeating),
+        });
+    }
+}
+
+pub fn update_prop_spawner<QF: QueryFilter>(
+    mut commands: Commands,
+    mut spawner: ResMut<PropSpawner>,
+    time: Res<Time>,
+    map: Res<CurrentMap>,
+    gk: Res<CurrentGameKind>,
+    mut match_rng: ResMut<MatchRng>,
+    q_players: Query<&Position, (With<Player>, Without<Dead>, QF)>,
+    q_props: Query<(), (With<Prop>, QF)>,
+) {
+    spawner.timer.tick(time.delta());
+    if !spawner.timer.just_finished() {
+        return;
+    }
+    let Some(rules) = &map.prop_spawning else {
+        return;
+    };
+    if q_props.iter().count() >= rules.max_props || rules.kinds.is_empty() {
+        return;
+    }
+    let player_positions: Vec<Vec2> = q_players.iter().map(|p| p.0).collect();
+    if player_positions.is_empty() {
+        return;
+    }
+    let rng = match_rng.stream(RngStream::Props);
+    let center = player_positions[rng.random_range(0..player_positions.len())];
+    let kind = rules.kinds[rng.random_range(0..rules.kinds.len())];
+    if let Some(pos) = map.spawn_point_near(center, PROP_SPAWN_RING.start, PROP_SPAWN_RING.end, rng)
+    {
+        spawn_prop(&mut commands, gk.0.unwrap(), kind, pos);
+    }
+}
+
+pub fn add_non_replicated_prop_components<QF: QueryFilter>(
+    trigger: On<Add, Prop>,
+    mut commands: Commands,
+    q_prop: Query<&Prop, QF>,
+) {
+    if let Ok(prop) = q_prop.get(trigger.entity) {
+        commands.entity(trigger.entity).insert((
+            Name::from(format!("{:?}", prop.kind)),
+            CommonColliderBundle::from(*prop),
+            RecentlyCollided::default(),
+        ));
+    }
+}
+
+/// Broken props leave their loot behind where they stood, rolled with the luck of whoever
+/// broke them
+pub fn on_prop_death<QF: QueryFilter>(
+    trigger: On<Add, Dead>,
+    mut commands: Commands,
+    gk: Res<CurrentGameKind>,
+    tables: Res<LootTables>,
+    mut rng: ResMut<MatchRng>,
+    q_prop: Query<(&Prop, &Position, Option<&KilledBy>), QF>,
+    q_created: Query<&CreatedBy>,
+    q_parent: Query<&ChildOf>,
+    q_player: Query<(), With<Player>>,
+    q_luck: Query<&Luck, With<Player>>,
+) {
+    let Ok((prop, pos, m_killer)) = q_prop.get(trigger.entity) else {
+        return;
+    };
+    if let Some(table) = tables.prop(prop.kind) {
+        let breaker =
+            m_killer.and_then(|k| responsible_player(k.0, &q_created, &q_parent, &q_player));
+        let luck = luck_of(breaker, &q_luck);
+        drop_loot(
+            &mut commands,
+            &gk,
+            table,
+            luck,
+            rng.stream(RngStream::Loot),
+            pos.0,
+        );
+    }
+    commands.entity(trigger.entity).despawn();
+}
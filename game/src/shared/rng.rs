@@ -0,0 +1,119 @@
+//! Every random thing that happens in a match comes from the match seed in `GameRules`.
+//!
+//! The seed gets picked when the lobby opens, and the server sends it out to the clients along
+//! with the rest of the rules, so everybody starts a match from the same place. Each subsystem
+//! gets its own stream derived from the seed, so that (for example) a new kind of loot roll
+//! doesn't shift where every enemy after it spawns.
+//!
+//! `MatchRng` is for the systems that only run where the game is simulated. Anything that gets
+//! predicted (and so can be rolled back and replayed) draws from a `RollbackRng` on the entity
+//! instead. That's a component, so lightyear restores it along with everything else, and the
+//! replayed tick rolls the same numbers as the first time through
+use bevy::{platform::collections::HashMap, prelude::*};
+use lightyear::prelude::*;
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::{game_rules::GameRules, states::AppState};
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+pub struct RngProtocolPlugin;
+
+impl Plugin for RngProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_component::<RollbackRng>().add_prediction();
+    }
+}
+
+pub struct SharedRngPlugin;
+
+impl Plugin for SharedRngPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::LoadingLevel), seed_match_rng);
+    }
+}
+
+/// The finalizer from SplitMix64. It spreads nearby inputs (like a seed and the seed plus one)
+/// out into completely different outputs
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum RngStream {
+    Spawning,
+    Bosses,
+    Props,
+    Loot,
+    Players,
+}
+
+impl RngStream {
+    pub fn seed_from(&self, match_seed: u64) -> u64 {
+        mix(match_seed ^ (*self as u64 + 1).wrapping_mul(GOLDEN_GAMMA))
+    }
+}
+
+/// A separate stream of random numbers for each subsystem, all derived from the match seed
+#[derive(Resource, Debug, Clone)]
+pub struct MatchRng {
+    pub seed: u64,
+    streams: HashMap<RngStream, StdRng>,
+}
+
+impl MatchRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::default(),
+        }
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        let seed = self.seed;
+        self.streams
+            .entry(stream)
+            .or_insert_with(|| StdRng::seed_from_u64(stream.seed_from(seed)))
+    }
+}
+
+pub fn seed_match_rng(mut commands: Commands, rules: Res<GameRules>) {
+    info!("Starting the match with seed {}", rules.seed);
+    commands.insert_resource(MatchRng::new(rules.seed));
+}
+
+/// A small RNG that lives on an entity, for random draws in predicted code.
+///
+/// It's just a SplitMix64 counter, so the whole state is one number that's cheap to replicate
+/// and to compare for rollbacks
+#[derive(Component, Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Reflect)]
+pub struct RollbackRng {
+    pub state: u64,
+}
+
+impl RollbackRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl RngCore for RollbackRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix(self.state)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for chunk in dst.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
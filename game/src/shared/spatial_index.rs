@@ -0,0 +1,191 @@
+//! A spatial hash of everything with a collider, rebuilt every fixed tick.
+//!
+//! Anything that needs to find things near a point (the closest enemy, everything in a blast,
+//! the biggest pile of enemies) should ask this instead of scanning every entity. Entries are
+//! tagged with their collision layer memberships, so every query takes the `ColliderTypes` it
+//! cares about
+use avian2d::prelude::*;
+use bevy::{ecs::query::QueryFilter, platform::collections::HashMap, prelude::*};
+
+use crate::shared::damage::Dead;
+
+/// Roughly the size of the biggest enemy, so most queries only touch a handful of cells
+pub const SPATIAL_CELL_SIZE: f32 = 128.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    pub pos: Vec2,
+    pub layers: LayerMask,
+}
+
+impl SpatialEntry {
+    fn matches(&self, filter: LayerMask) -> bool {
+        (self.layers & filter) != LayerMask::NONE
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<SpatialEntry>>,
+    /// The corners of the area that has anything in it, so searches know when to give up
+    min_cell: IVec2,
+    max_cell: IVec2,
+    len: usize,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new(SPATIAL_CELL_SIZE)
+    }
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            min_cell: IVec2::MAX,
+            max_cell: IVec2::MIN,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Empties the index, but holds onto the cells so that rebuilding doesn't reallocate
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.min_cell = IVec2::MAX;
+        self.max_cell = IVec2::MIN;
+        self.len = 0;
+    }
+
+    pub fn insert(&mut self, entity: Entity, pos: Vec2, layers: impl Into<LayerMask>) {
+        let cell = self.cell_of(pos);
+        self.min_cell = self.min_cell.min(cell);
+        self.max_cell = self.max_cell.max(cell);
+        self.len += 1;
+        self.cells.entry(cell).or_default().push(SpatialEntry {
+            entity,
+            pos,
+            layers: layers.into(),
+        });
+    }
+
+    pub fn cell_of(&self, pos: Vec2) -> IVec2 {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+
+    /// The closest entry on any of the given layers
+    pub fn nearest(&self, pos: Vec2, filter: impl Into<LayerMask>) -> Option<SpatialEntry> {
+        self.k_nearest(pos, 1, filter).into_iter().next()
+    }
+
+    /// Up to `k` of the closest entries on any of the given layers, closest first
+    pub fn k_nearest(
+        &self,
+        pos: Vec2,
+        k: usize,
+        filter: impl Into<LayerMask>,
+    ) -> Vec<SpatialEntry> {
+        let filter = filter.into();
+        let mut found: Vec<(f32, SpatialEntry)> = Vec::new();
+        if k == 0 || self.is_empty() {
+            return Vec::new();
+        }
+        let center = self.cell_of(pos);
+        for r in 0..=self.max_ring(center) {
+            for cell in ring(center, r) {
+                let Some(entries) = self.cells.get(&cell) else {
+                    continue;
+                };
+                for entry in entries.iter().filter(|e| e.matches(filter)) {
+                    found.push((entry.pos.distance_squared(pos), *entry));
+                }
+            }
+            found.sort_by(|a, b| a.0.total_cmp(&b.0));
+            found.truncate(k);
+            // Anything in a ring we haven't looked at yet is at least this far away
+            let unsearched = r as f32 * self.cell_size;
+            if found.len() == k && found[k - 1].0 <= unsearched * unsearched {
+                break;
+            }
+        }
+        found.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// Every entry on any of the given layers within `radius` of `pos`, in no particular order
+    pub fn within_radius(
+        &self,
+        pos: Vec2,
+        radius: f32,
+        filter: impl Into<LayerMask>,
+    ) -> impl Iterator<Item = SpatialEntry> + '_ {
+        let filter = filter.into();
+        let min = self.cell_of(pos - Vec2::splat(radius));
+        let max = self.cell_of(pos + Vec2::splat(radius));
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |e| e.matches(filter) && e.pos.distance_squared(pos) <= radius * radius)
+            .copied()
+    }
+
+    /// The center of the cell with the most entries on the given layers, and how many it has
+    pub fn densest_cell(&self, filter: impl Into<LayerMask>) -> Option<(Vec2, usize)> {
+        let filter = filter.into();
+        self.cells
+            .iter()
+            .map(|(cell, entries)| (cell, entries.iter().filter(|e| e.matches(filter)).count()))
+            .filter(|(_, count)| *count > 0)
+            // Break ties by cell so that the answer doesn't depend on the hash order
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.to_array().cmp(&a.0.to_array())))
+            .map(|(cell, count)| ((cell.as_vec2() + 0.5) * self.cell_size, count))
+    }
+
+    /// How many rings out from `center` it takes to cover everything in the index
+    fn max_ring(&self, center: IVec2) -> i32 {
+        let to_min = (center - self.min_cell).abs().max_element();
+        let to_max = (center - self.max_cell).abs().max_element();
+        to_min.max(to_max)
+    }
+}
+
+/// The cells that are exactly `r` cells away from `center`
+fn ring(center: IVec2, r: i32) -> impl Iterator<Item = IVec2> {
+    (-r..=r).flat_map(move |x| {
+        (-r..=r).filter_map(move |y| {
+            (x.abs() == r || y.abs() == r).then_some(center + IVec2::new(x, y))
+        })
+    })
+}
+
+pub struct SharedSpatialIndexPlugin;
+
+impl Plugin for SharedSpatialIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialIndex>();
+    }
+}
+
+/// Rebuilds the index from scratch. This should run before anything that reads from it
+pub fn rebuild_spatial_index<QF: QueryFilter>(
+    mut index: ResMut<SpatialIndex>,
+    q_colliders: Query<(Entity, &Position, &CollisionLayers), (Without<Dead>, QF)>,
+) {
+    index.clear();
+    for (ent, pos, layers) in &q_colliders {
+        index.insert(ent, pos.0, layers.memberships);
+    }
+}
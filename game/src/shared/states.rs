@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::shared::game_kinds::is_single_player;
+
 /// Handles all of the logic that is relevant to the game loop.
 #[derive(States, Component, Clone, PartialEq, Eq, Hash, Debug, Default)]
 pub enum InGameState {
@@ -40,6 +42,24 @@ pub enum PauseState {
 pub struct SharedStatesPlugin;
 impl Plugin for SharedStatesPlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<AppState>().init_state::<InGameState>();
+        app.init_state::<AppState>()
+            .init_state::<InGameState>()
+            .init_state::<PauseState>()
+            .add_systems(
+                OnEnter(InGameState::Paused),
+                pause_simulation.run_if(is_single_player),
+            )
+            .add_systems(OnExit(InGameState::Paused), resume_simulation);
     }
 }
+
+/// Only single player is allowed to actually stop the game. Fixed time is driven off of
+/// virtual time, so pausing it here stops every `FixedUpdate` system from ticking, rather
+/// than having to gate each of them on the `InGameState`
+fn pause_simulation(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_simulation(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
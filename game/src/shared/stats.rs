@@ -7,7 +7,7 @@ pub mod editor;
 pub mod xp;
 
 use components::*;
-use xp::LevelManager;
+use xp::{ApplyXPMessage, LevelManager, LevelUpMessage};
 
 use crate::utils::AssetFolder;
 
@@ -30,6 +30,8 @@ impl Plugin for StatsProtocolPlugin {
         app.register_component::<ProjectileSpeed>().add_prediction();
         app.register_component::<XPGain>().add_prediction();
         app.register_component::<LevelManager>().add_prediction();
+        app.add_message::<ApplyXPMessage>();
+        app.add_message::<LevelUpMessage>();
     }
 }
 
@@ -118,6 +120,33 @@ impl RawStatsList {
         crate::utils::read_ron::<RawStatsList>(new_path)
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = &StatKind> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut StatKind> {
+        self.0.iter_mut()
+    }
+
+    /// Scales the stats of an enemy that's about to be spawned. Difficulty and elite promotion
+    /// both change the same three stats, just by different amounts
+    pub fn scale_enemy_stats(&mut self, health: f32, damage: f32, speed: f32) {
+        for stat in self.iter_mut() {
+            match stat {
+                StatKind::Health(hp) => {
+                    hp.max *= health;
+                    hp.current *= health;
+                }
+                StatKind::Damage(d) => d.0 *= damage,
+                StatKind::MS(ms) => {
+                    ms.current *= speed;
+                    ms.cap *= speed;
+                }
+                _ => {}
+            }
+        }
+    }
+
     pub fn apply_to_character(mut self, ent: Entity, comms: &mut Commands) {
         let mut ec = comms.entity(ent);
         for sk in self.0.drain(..) {
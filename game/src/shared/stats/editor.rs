@@ -1,6 +1,13 @@
 use super::RawStatsList;
 use crate::{
-    shared::{enemies::EnemyKind, players::CharacterKind, weapons::WeaponKind},
+    shared::{
+        enemies::{
+            EnemyKind,
+            definitions::{DEFINITION_FILE, EnemyRegistry},
+        },
+        players::CharacterKind,
+        weapons::WeaponKind,
+    },
     utils::AssetFolder,
 };
 use bevy::prelude::*;
@@ -55,9 +62,17 @@ impl StatsEditor {
         let _write = std::fs::write(stats_path, ron_string);
     }
 
-    pub fn save_enemy(&mut self) {
+    /// Enemy stats live inside of the enemy's definition, so the whole definition gets written back
+    /// out, and the registry is updated so that the next spawn picks up the change
+    pub fn save_enemy(&mut self, registry: &mut EnemyRegistry) {
         if let Some(ref list) = self.c_enemy_stats {
-            Self::save_procedure(list, self.c_enemy);
+            let folder = registry.folder(self.c_enemy);
+            let def = registry.get_mut(self.c_enemy);
+            def.stats = list.clone();
+            let def_path = format!("assets/{}", folder.to_path(DEFINITION_FILE.into()));
+            let ron_string = ron::ser::to_string_pretty(def, PrettyConfig::new())
+                .expect("Failed to serialize enemy definition");
+            let _write = std::fs::write(def_path, ron_string);
         }
         self.save_enemy = false;
     }
@@ -77,7 +92,7 @@ impl StatsEditor {
     }
 }
 
-fn load_new_stats(mut editor: ResMut<StatsEditor>) {
+fn load_new_stats(mut editor: ResMut<StatsEditor>, registry: Res<EnemyRegistry>) {
     let load_char = match editor.p_char {
         Some(c) => editor.c_char != c,
         None => true,
@@ -93,8 +108,15 @@ fn load_new_stats(mut editor: ResMut<StatsEditor>) {
         None => true,
     };
 
+    // Not every id belongs to an enemy, so fall back to the first one that does
+    if !registry.contains(editor.c_enemy) {
+        editor.c_enemy = registry
+            .iter()
+            .next()
+            .map_or_else(EnemyKind::default, |(k, _)| k);
+    }
     if load_enemy {
-        let stats = RawStatsList::import_stats(editor.c_enemy);
+        let stats = registry.get(editor.c_enemy).stats.clone();
         editor.c_enemy_stats = Some(stats);
         editor.p_enemy = Some(editor.c_enemy);
     }
@@ -111,12 +133,12 @@ fn load_new_stats(mut editor: ResMut<StatsEditor>) {
     }
 }
 
-fn save_changes_to_stats(mut editor: ResMut<StatsEditor>) {
+fn save_changes_to_stats(mut editor: ResMut<StatsEditor>, mut registry: ResMut<EnemyRegistry>) {
     if editor.save_char {
         editor.save_char();
     }
     if editor.save_enemy {
-        editor.save_enemy();
+        editor.save_enemy(&mut registry);
     }
     if editor.save_weapon {
         editor.save_weapon();
@@ -4,10 +4,15 @@ use serde::{Deserialize, Serialize};
 use crate::shared::{
     game_kinds::{CurrentGameKind, MultiPlayerComponentOptions},
     game_object_spawning::spawn_game_object,
+    game_rules::{GameRules, XPSharing},
     players::Player,
     stats::components::XPGain,
 };
 
+/// Tracks the level of whoever is gaining XP.
+///
+/// With `XPSharing::Shared`, there's one of these on its own entity for the whole party.
+/// With `XPSharing::PerPlayer`, each player has their own one attached to them
 #[derive(Component, Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
 pub struct LevelManager {
     pub c_level: u8,
@@ -28,34 +33,147 @@ impl Default for LevelManager {
     }
 }
 
-#[derive(Message)]
+impl LevelManager {
+    pub fn new(curve: &LevelCurve) -> Self {
+        Self {
+            next_max: curve.xp_to_reach(2),
+            ..default()
+        }
+    }
+
+    /// Adds the XP, and keeps leveling up for as long as there's enough of it.
+    ///
+    /// Returns each of the levels that were reached along the way, in order
+    pub fn add_xp(&mut self, amount: f32, curve: &LevelCurve) -> Vec<u8> {
+        self.c_xp += amount;
+        let mut reached = Vec::new();
+        while self.c_xp >= self.next_max && self.c_level < u8::MAX {
+            self.c_level += 1;
+            self.prev_max = self.next_max;
+            self.next_max = curve.xp_to_reach(self.c_level.saturating_add(1));
+            reached.push(self.c_level);
+        }
+        reached
+    }
+}
+
+/// How much XP it takes to reach each level, as read from `assets/game_rules/level_curve.ron`
+#[derive(Resource, Debug, Clone, Serialize, Deserialize, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct LevelCurve {
+    /// The total XP needed to reach each level, starting with level 2
+    pub thresholds: Vec<f32>,
+    /// Past the end of the table, each level costs this much more than the one before it
+    pub step_past_table: f32,
+}
+
+impl LevelCurve {
+    pub const PATH: &'static str = "assets/game_rules/level_curve.ron";
+
+    pub fn import() -> Self {
+        crate::utils::read_ron::<LevelCurve>(Self::PATH.into())
+    }
+
+    /// The total XP needed to get to the given level
+    pub fn xp_to_reach(&self, level: u8) -> f32 {
+        if level <= 1 {
+            return 0.0;
+        }
+        let idx = (level - 2) as usize;
+        match self.thresholds.get(idx) {
+            Some(threshold) => *threshold,
+            None => {
+                let last = self.thresholds.last().copied().unwrap_or(0.0);
+                let past = idx + 1 - self.thresholds.len();
+                last + self.step_past_table * past as f32
+            }
+        }
+    }
+}
+
+pub fn add_level_curve(mut commands: Commands) {
+    commands.insert_resource(LevelCurve::import());
+}
+
+#[derive(Message, Debug, Clone, Copy)]
 pub struct ApplyXPMessage {
+    /// The player that picked up the XP
+    pub collector: Entity,
     pub amount: f32,
 }
 
+/// Written once for every level that gets reached, so a big gem that crosses a few thresholds
+/// at once writes a few of these
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct LevelUpMessage {
+    /// The entity with the `LevelManager` on it. With `XPSharing::PerPlayer`, this is the player
+    pub level_manager: Entity,
+    pub level: u8,
+}
+
+/// Routes XP to the right level manager, depending on the XP sharing rule for this game.
+///
+/// The collector's `XPGain` scales the XP either way
 pub fn add_xp<QF: QueryFilter>(
     mut mess: MessageReader<ApplyXPMessage>,
-    mut q_level: Single<&mut LevelManager>,
-    _q_stats: Query<&XPGain, (With<Player>, QF)>,
+    mut level_ups: MessageWriter<LevelUpMessage>,
+    rules: Res<GameRules>,
+    curve: Res<LevelCurve>,
+    mut q_party_level: Query<(Entity, &mut LevelManager), (Without<Player>, QF)>,
+    mut q_player_level: Query<(Entity, &mut LevelManager), (With<Player>, QF)>,
+    q_stats: Query<&XPGain, (With<Player>, QF)>,
 ) {
     for xp in mess.read() {
-        q_level.c_xp += xp.amount;
-        if q_level.c_xp >= q_level.next_max {
-            q_level.c_level += 1;
-            q_level.prev_max = q_level.next_max;
-            q_level.next_max = (q_level.c_level as f32 * 10.0).powf(1.5);
+        let gain = q_stats.get(xp.collector).map_or(1.0, |g| g.0);
+        let amount = xp.amount * gain;
+        let mut apply = |level_manager: Entity, mut level: Mut<LevelManager>| {
+            for reached in level.add_xp(amount, &curve) {
+                level_ups.write(LevelUpMessage {
+                    level_manager,
+                    level: reached,
+                });
+            }
+        };
+        match rules.xp_sharing {
+            XPSharing::Shared => {
+                for (ent, level) in &mut q_party_level {
+                    apply(ent, level);
+                }
+            }
+            XPSharing::PerPlayer => {
+                if let Ok((ent, level)) = q_player_level.get_mut(xp.collector) {
+                    apply(ent, level);
+                }
+            }
         }
     }
 }
 
-pub fn add_level_manager(mut commands: Commands, gk: Res<CurrentGameKind>) {
-    let _lm = spawn_game_object(
-        &mut commands,
-        gk.0.unwrap(),
-        MultiPlayerComponentOptions {
-            pred: true,
-            interp: false,
-        },
-        (LevelManager::default(), Name::from("Level Manager")),
-    );
+/// Needs to run after the players have been spawned, so that each of them can get a level
+/// manager if we're leveling per player
+pub fn add_level_manager<QF: QueryFilter>(
+    mut commands: Commands,
+    gk: Res<CurrentGameKind>,
+    rules: Res<GameRules>,
+    curve: Res<LevelCurve>,
+    q_players: Query<Entity, (With<Player>, QF)>,
+) {
+    match rules.xp_sharing {
+        XPSharing::Shared => {
+            spawn_game_object(
+                &mut commands,
+                gk.0.unwrap(),
+                MultiPlayerComponentOptions {
+                    pred: true,
+                    interp: false,
+                },
+                (LevelManager::new(&curve), Name::from("Level Manager")),
+            );
+        }
+        XPSharing::PerPlayer => {
+            for p_ent in &q_players {
+                commands.entity(p_ent).insert(LevelManager::new(&curve));
+            }
+        }
+    }
 }
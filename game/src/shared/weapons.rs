@@ -41,6 +41,12 @@ pub struct Weapon {
     activity_pattern: WeaponActivityPattern,
 }
 
+impl Weapon {
+    pub fn kind(&self) -> WeaponKind {
+        self.kind
+    }
+}
+
 impl From<Weapon> for MultiPlayerComponentOptions {
     fn from(value: Weapon) -> Self {
         Self {
@@ -50,7 +56,9 @@ impl From<Weapon> for MultiPlayerComponentOptions {
     }
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Reflect, Default)]
+#[derive(
+    Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect, Default,
+)]
 pub enum WeaponKind {
     #[default]
     DiceGuard,
@@ -59,6 +67,18 @@ pub enum WeaponKind {
     FlurryOfBlows,
 }
 
+impl WeaponKind {
+    /// What the weapon gets called anywhere that the player can see it
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::DiceGuard => "Dice Guard",
+            Self::ThrowHands => "Throw Hands",
+            Self::PaddleBack => "Paddle Back",
+            Self::FlurryOfBlows => "Flurry of Blows",
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Reflect, Clone, Copy)]
 pub enum WeaponActivityPattern {
     AlwaysOn,
@@ -63,6 +63,7 @@ pub fn dice_guard_activate<QF: QueryFilter>(
                     c_angle: angle,
                     radius: r,
                 },
+                faction: ProjectileFaction::Player,
             };
             let pos = par_pos.0 + Vec2::from_angle(angle) * r;
             trace!("Found angle to be {angle}, position is {:?}", pos);
@@ -77,7 +78,10 @@ pub fn dice_guard_activate<QF: QueryFilter>(
                     CreatedBy(dg_ent),
                     *dam,
                     *size,
-                    AppliesCollisionEffect::new([ColliderTypes::Enemy].into(), ApplyDamage),
+                    AppliesCollisionEffect::new(
+                        [ColliderTypes::Enemy, ColliderTypes::Prop].into(),
+                        ApplyDamage,
+                    ),
                 ),
             );
         }
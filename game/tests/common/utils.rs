@@ -4,7 +4,11 @@ use snappa_survivors::{
     build::{build_game_client_app, build_game_server_app},
     client::game_client::{GameClient, GameClientConfig},
 };
-use std::time::Duration;
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
 
 pub fn setup_test_client() -> App {
     let mut app = App::new();
@@ -64,3 +68,36 @@ pub fn setup_multiplayer_connected_apps() -> (App, App) {
 
     (server_app, client_app)
 }
+
+/// A path under the temp dir that no other test (or run of the tests) will use, which gets
+/// removed along with anything under it once this is dropped
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        let unique = format!(
+            "snappa_{}_{}_{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed),
+            name
+        );
+        Self(std::env::temp_dir().join(unique))
+    }
+}
+
+impl std::ops::Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // Whatever the test left here, if anything
+        let _ = std::fs::remove_dir_all(&self.0);
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use snappa_survivors::shared::{
+    demo::*, drops::PickupKind, game_rules::GameRules, match_clock::MatchOutcome,
+};
+
+fn demo_with_frames(times: &[f32]) -> DemoFile {
+    let mut demo = DemoFile::new(GameRules::default());
+    demo.frames = times
+        .iter()
+        .map(|time| DemoFrame {
+            time: *time,
+            entities: Vec::new(),
+        })
+        .collect();
+    demo
+}
+
+#[test]
+fn frame_at_finds_the_latest_snapshot() {
+    let demo = demo_with_frames(&[0.0, 0.25, 0.5, 0.75]);
+    assert_eq!(demo.frame_at(0.0), 0);
+    assert_eq!(demo.frame_at(0.3), 1);
+    assert_eq!(demo.frame_at(0.5), 2);
+    assert_eq!(demo.frame_at(10.0), 3);
+    assert_eq!(demo.duration(), 0.75);
+}
+
+#[test]
+fn events_before_only_looks_back() {
+    let mut demo = demo_with_frames(&[0.0, 10.0]);
+    demo.events = [1.0, 4.0, 6.0, 9.0]
+        .into_iter()
+        .map(|time| DemoEvent {
+            time,
+            kind: DemoEventKind::LevelUp { level: 2 },
+        })
+        .collect();
+    let times: Vec<f32> = demo.events_before(6.0, 3.0).map(|e| e.time).collect();
+    assert_eq!(times, vec![4.0, 6.0]);
+}
+
+#[test]
+fn entities_get_rounded_down_to_size() {
+    let entity = DemoEntity::new(
+        Entity::from_raw_u32(7).unwrap(),
+        DemoEntityKind::Player,
+        Vec2::new(10.4, -3.6),
+        Some((30.0, 40.0)),
+    );
+    assert_eq!(entity.pos, IVec2::new(10, -4));
+    assert_eq!(entity.health, Some(75));
+}
+
+#[test]
+fn demos_survive_a_round_trip() {
+    let mut demo = demo_with_frames(&[0.0, 0.25]);
+    demo.frames[1].entities.push(DemoEntity::new(
+        Entity::from_raw_u32(3).unwrap(),
+        DemoEntityKind::Pickup(PickupKind::Magnet),
+        Vec2::new(100.0, 50.0),
+        None,
+    ));
+    demo.events.push(DemoEvent {
+        time: 0.25,
+        kind: DemoEventKind::MatchEnded {
+            outcome: MatchOutcome::Victory,
+        },
+    });
+    let path = std::env::temp_dir().join("snappa_demo_round_trip.demo");
+    demo.save(&path).expect("the demo should save");
+    let loaded = DemoFile::load(&path);
+    assert_eq!(loaded.frames, demo.frames);
+    assert_eq!(loaded.events, demo.events);
+}
+
+fn gem_at(id: u32, x: f32) -> DemoEntity {
+    DemoEntity::new(
+        Entity::from_raw_u32(id).unwrap(),
+        DemoEntityKind::XPGem,
+        Vec2::new(x, 0.0),
+        None,
+    )
+}
+
+#[test]
+fn deltas_only_hold_what_changed() {
+    let first = DemoFrame {
+        time: 0.0,
+        entities: vec![gem_at(1, 0.0), gem_at(2, 10.0), gem_at(3, 20.0)],
+    };
+    let second = DemoFrame {
+        time: 0.25,
+        entities: vec![gem_at(1, 0.0), gem_at(2, 15.0), gem_at(4, 30.0)],
+    };
+    let last: HashMap<u64, DemoEntity> = first.entities.iter().map(|e| (e.id, *e)).collect();
+    let delta = DemoFrameDelta::between(&last, &second);
+    assert_eq!(delta.changed, vec![gem_at(2, 15.0), gem_at(4, 30.0)]);
+    assert_eq!(delta.removed, vec![gem_at(3, 20.0).id]);
+}
+
+#[test]
+fn a_cut_off_demo_keeps_what_was_written() {
+    let path = std::env::temp_dir().join("snappa_demo_cut_off.demo");
+    let mut writer = DemoWriter::create(&path, GameRules::default()).unwrap();
+    for i in 0..3 {
+        writer
+            .write_frame(&DemoFrame {
+                time: i as f32 * 0.25,
+                entities: vec![gem_at(1, i as f32), gem_at(2, 5.0)],
+            })
+            .unwrap();
+    }
+    drop(writer);
+    // Lose the back half of the last snapshot, like a server going down in the middle of it
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 2]).unwrap();
+
+    let demo = DemoFile::load(&path);
+    assert_eq!(demo.frames.len(), 2);
+    assert_eq!(
+        demo.frames[1].entities,
+        vec![gem_at(1, 1.0), gem_at(2, 5.0)]
+    );
+}
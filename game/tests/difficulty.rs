@@ -0,0 +1,55 @@
+use snappa_survivors::shared::{
+    enemies::{definitions::EnemyRegistry, spawner::target_enemy_count},
+    game_rules::*,
+    stats::StatKind,
+};
+
+#[test]
+fn difficulty_table_is_ordered() {
+    let table = DifficultyTable::import();
+    let (easy, normal, hard) = (
+        table.get(Difficulty::Easy),
+        table.get(Difficulty::Normal),
+        table.get(Difficulty::Hard),
+    );
+
+    assert_eq!(normal, DifficultyModifiers::default());
+    assert!(easy.enemy_health < normal.enemy_health && normal.enemy_health < hard.enemy_health);
+    assert!(easy.enemy_damage < normal.enemy_damage && normal.enemy_damage < hard.enemy_damage);
+    assert!(easy.spawn_rate < normal.spawn_rate && normal.spawn_rate < hard.spawn_rate);
+    // Harder games give out less XP per kill, since there are more kills to be had
+    assert!(easy.xp_yield > normal.xp_yield && normal.xp_yield > hard.xp_yield);
+}
+
+#[test]
+fn hard_enemies_have_more_health() {
+    let table = DifficultyTable::import();
+    let registry = EnemyRegistry::load();
+    let faceless = registry
+        .kind("faceless")
+        .expect("The faceless man should exist");
+    let max_health = |difficulty: Difficulty| {
+        let mut stats = registry.get(faceless).stats.clone();
+        table.get(difficulty).apply_to_enemy_stats(&mut stats);
+        stats
+            .iter()
+            .find_map(|s| match s {
+                StatKind::Health(hp) => Some(hp.max),
+                _ => None,
+            })
+            .expect("Enemies should have health")
+    };
+
+    assert!(max_health(Difficulty::Easy) < max_health(Difficulty::Normal));
+    assert!(max_health(Difficulty::Normal) < max_health(Difficulty::Hard));
+}
+
+#[test]
+fn spawn_target_grows_with_time_and_players() {
+    let start = target_enemy_count(1, 0.0, 1.0);
+    assert!(start > 0);
+    assert!(target_enemy_count(1, 5.0, 1.0) > start);
+    assert!(target_enemy_count(4, 0.0, 1.0) > start);
+    assert!(target_enemy_count(1, 0.0, 1.5) > start);
+    assert_eq!(target_enemy_count(0, 10.0, 1.0), 0);
+}
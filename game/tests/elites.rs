@@ -0,0 +1,70 @@
+use rand::{SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::enemies::elites::*;
+
+#[test]
+fn elite_table_loads() {
+    let table = EliteTable::import();
+    assert!(!table.affixes.is_empty());
+    assert!(table.chance_at(0.0) <= table.chance_at(10.0));
+    assert!(table.chance_at(1000.0) <= table.max_chance);
+}
+
+#[test]
+fn elites_never_repeat_affixes() {
+    let mut table = EliteTable::import();
+    // Promote everything, so every roll tells us something
+    table.chance = 1.0;
+    table.max_chance = 1.0;
+    let mut rng = StdRng::seed_from_u64(11);
+    for _ in 0..200 {
+        let elite = table
+            .roll(0.0, &mut rng)
+            .expect("Everything should be an elite");
+        assert!(!elite.affixes.is_empty());
+        assert!(elite.affixes.len() <= table.max_affixes as usize);
+        for (i, affix) in elite.affixes.iter().enumerate() {
+            assert!(
+                elite.affixes[i + 1..]
+                    .iter()
+                    .all(|other| other.name() != affix.name()),
+                "Rolled {} twice",
+                affix.name()
+            );
+        }
+        // Elites are always at least as tough and rewarding as the base promotion
+        assert!(elite.stats.health >= table.base.health);
+        assert!(elite.stats.xp >= table.base.xp);
+    }
+}
+
+#[test]
+fn no_chance_means_no_elites() {
+    let mut table = EliteTable::import();
+    table.chance = 0.0;
+    table.chance_per_min = 0.0;
+    let mut rng = StdRng::seed_from_u64(12);
+    assert!((0..100).all(|_| table.roll(5.0, &mut rng).is_none()));
+}
+
+#[test]
+fn affixes_do_what_they_say() {
+    let elite = Elite {
+        affixes: vec![
+            EliteAffix::Vampiric { lifesteal: 0.5 },
+            EliteAffix::Explosive {
+                radius: 100.0,
+                damage: 2.0,
+            },
+        ],
+        stats: StatMultipliers::default(),
+    };
+    assert_eq!(elite.lifesteal(), 0.5);
+    assert_eq!(elite.explosion(), Some((100.0, 2.0)));
+
+    let plain = Elite {
+        affixes: vec![EliteAffix::Hasted],
+        stats: StatMultipliers::default(),
+    };
+    assert_eq!(plain.lifesteal(), 0.0);
+    assert_eq!(plain.explosion(), None);
+}
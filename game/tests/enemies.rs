@@ -0,0 +1,333 @@
+use avian2d::prelude::{ColliderDisabled, Position};
+use bevy::{
+    ecs::entity::Entity,
+    math::{Rect, Vec2},
+    prelude::*,
+};
+use lightyear::prelude::PeerId;
+use rand::{SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::{
+    enemies::{
+        Enemy, EnemyKind, EnemyState, behaviours::*, bosses::*, definitions::*, lod::*,
+        navigation::*, spawner::pick_enemy_kind, targeting::*,
+    },
+    players::{Player, VIEW_EXTENT},
+    stats::StatKind,
+};
+
+mod common;
+use common::TempPath;
+
+#[test]
+fn registry_finds_every_enemy() {
+    let registry = EnemyRegistry::load();
+    assert!(!registry.is_empty());
+    for (_kind, def) in registry.iter() {
+        // The state machine can't move an enemy without a movement speed
+        assert!(
+            def.stats.iter().any(|s| matches!(s, StatKind::MS(_))),
+            "{} has no movement speed",
+            def.name
+        );
+        assert!(def.spawning.weight >= 0.0);
+    }
+}
+
+#[test]
+fn kinds_come_from_the_definitions() {
+    let registry = EnemyRegistry::load();
+    // These are saved in demos, so they can't move around when the folders change
+    assert_eq!(registry.kind("blob"), Some(EnemyKind(0)));
+    assert_eq!(registry.kind("ogre"), Some(EnemyKind(6)));
+    for (kind, def) in registry.iter() {
+        assert_eq!(kind, def.id);
+        assert!(registry.contains(kind));
+    }
+    assert!(!registry.contains(EnemyKind(u16::MAX)));
+}
+
+#[test]
+#[should_panic(expected = "both have the id")]
+fn duplicate_ids_are_rejected() {
+    // Gets cleaned up as the panic unwinds
+    let root = TempPath::new("duplicate_enemy_ids");
+    for folder in ["first", "second"] {
+        let dir = root.join(folder);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy("assets/enemies/blob/enemy.ron", dir.join(DEFINITION_FILE)).unwrap();
+    }
+    EnemyRegistry::load_from(root.to_str().unwrap());
+}
+
+#[test]
+fn heckler_keeps_its_distance() {
+    let registry = EnemyRegistry::load();
+    let heckler = registry.kind("heckler").expect("The heckler should exist");
+    let ranged = registry
+        .get(heckler)
+        .behaviour
+        .ranged_attack()
+        .expect("Hecklers should have a ranged attack");
+    assert!(ranged.keep_away < ranged.range);
+
+    assert_eq!(ranged.approach(ranged.range + 1.0), 1.0);
+    assert_eq!(
+        ranged.approach((ranged.range + ranged.keep_away) / 2.0),
+        0.0
+    );
+    assert_eq!(ranged.approach(ranged.keep_away - 1.0), -1.0);
+
+    let faceless = registry
+        .kind("faceless")
+        .expect("The faceless man should exist");
+    assert!(registry.get(faceless).behaviour.ranged_attack().is_none());
+}
+
+#[test]
+fn spawns_respect_unlock_time() {
+    let registry = EnemyRegistry::load();
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let kind = pick_enemy_kind(&registry, 0.0, &mut rng).expect("Something should spawn");
+        assert_eq!(registry.get(kind).spawning.after_mins, 0.0);
+    }
+}
+
+#[test]
+fn charger_winds_up_then_dashes() {
+    let charger = EnemyBehaviour::Charger(ChargerBehaviour {
+        trigger_range: 100.0,
+        windup: 0.5,
+        dash_time: 0.5,
+        dash_speed: 4.0,
+        recover: 0.5,
+    });
+    let mut state = charger.initial_state().expect("Chargers have a state");
+    let dt = 1.0 / 64.0;
+    let target = Vec2::new(50.0, 0.0);
+
+    // In range, so the first tick starts the wind up and stands still
+    assert_eq!(
+        state.step(&charger, Vec2::ZERO, target, 10.0, dt),
+        Some(Vec2::ZERO)
+    );
+    assert_eq!(state.phase, BehaviourPhase::WindUp);
+
+    let mut velo = Vec2::ZERO;
+    for _ in 0..40 {
+        velo = state.step(&charger, Vec2::ZERO, target, 10.0, dt).unwrap();
+    }
+    assert_eq!(state.phase, BehaviourPhase::Dash(Vec2::X));
+    assert_eq!(velo, Vec2::X * 40.0);
+}
+
+#[test]
+fn behaviour_replays_the_same_after_rollback() {
+    let swarmer = EnemyBehaviour::Swarmer(SwarmerBehaviour {
+        orbit_radius: 50.0,
+        orbit_speed: 2.0,
+        orbit_time: 1.0,
+        dive_time: 0.25,
+        dive_speed: 3.0,
+    });
+    let dt = 1.0 / 64.0;
+    let target = Vec2::new(40.0, 10.0);
+    let run = |mut state: EnemyBehaviourState| {
+        (0..200)
+            .map(|_| state.step(&swarmer, Vec2::ZERO, target, 20.0, dt))
+            .collect::<Vec<_>>()
+    };
+
+    // A rollback restores the state and replays the ticks, so it has to land in the same place
+    let start = swarmer.initial_state().expect("Swarmers have a state");
+    assert_eq!(run(start), run(start));
+    assert!(EnemyBehaviour::Melee.initial_state().is_none());
+}
+
+#[test]
+fn retargeting_needs_a_clear_improvement() {
+    let near = Entity::from_raw_u32(1).unwrap();
+    let far = Entity::from_raw_u32(2).unwrap();
+    let e_pos = Vec2::ZERO;
+    let current = (far, target_score(e_pos, Vec2::new(200.0, 0.0), 0.0));
+
+    // Only a little closer, so the enemy sticks with who it's chasing
+    let slightly = [(near, target_score(e_pos, Vec2::new(180.0, 0.0), 0.0))];
+    assert_eq!(pick_target(Some(current), slightly.into_iter()), Some(far));
+
+    // Much closer, so it switches
+    let much = [(near, target_score(e_pos, Vec2::new(50.0, 0.0), 0.0))];
+    assert_eq!(pick_target(Some(current), much.into_iter()), Some(near));
+
+    // With no target yet, it just takes the best one
+    assert_eq!(pick_target(None, slightly.into_iter()), Some(near));
+}
+
+#[test]
+fn threat_pulls_enemies_away_from_closer_players() {
+    let quiet = Entity::from_raw_u32(1).unwrap();
+    let loud = Entity::from_raw_u32(2).unwrap();
+    let e_pos = Vec2::ZERO;
+    let candidates = [
+        (quiet, target_score(e_pos, Vec2::new(100.0, 0.0), 0.0)),
+        (loud, target_score(e_pos, Vec2::new(250.0, 0.0), MAX_THREAT)),
+    ];
+    assert_eq!(pick_target(None, candidates.into_iter()), Some(loud));
+}
+
+#[test]
+fn flow_field_leads_around_walls() {
+    // A wall straight between the enemy and the player, with gaps at either end
+    let wall = Rect::new(-20.0, -200.0, 20.0, 200.0);
+    let field = FlowField::new(Vec2::new(300.0, 0.0), 1024.0, 32.0, &[wall]);
+
+    let start = Vec2::new(-300.0, 0.0);
+    let straight_line = 600.0 / 32.0 * 10.0;
+    let cost = field.cost(start).expect("The player should be reachable") as f32;
+    assert!(cost > straight_line, "The field went through the wall");
+
+    // Following the field gets to the player without ever touching the wall
+    let mut pos = start;
+    for _ in 0..200 {
+        let Some(dir) = field.direction(pos) else {
+            break;
+        };
+        pos += dir * 16.0;
+        assert!(!wall.contains(pos), "Walked into the wall at {pos}");
+    }
+    assert!(pos.distance(Vec2::new(300.0, 0.0)) < 64.0);
+}
+
+#[test]
+fn separation_pushes_crowds_apart() {
+    let pos = Vec2::ZERO;
+    let push = separation_force(pos, [Vec2::new(10.0, 0.0)].into_iter());
+    assert!(push.x < 0.0 && push.y.abs() < f32::EPSILON);
+
+    // Surrounded evenly, there's nowhere better to go
+    let even = [
+        Vec2::new(10.0, 0.0),
+        Vec2::new(-10.0, 0.0),
+        Vec2::new(0.0, 10.0),
+        Vec2::new(0.0, -10.0),
+    ];
+    assert!(separation_force(pos, even.into_iter()).length() < 0.001);
+
+    // Nobody in range, nothing to do
+    let far = [Vec2::new(SEPARATION_RADIUS * 2.0, 0.0)];
+    assert_eq!(separation_force(pos, far.into_iter()), Vec2::ZERO);
+    // However big the crowd, it never outweighs following the field by too much
+    let crowd = std::iter::repeat_n(Vec2::new(1.0, 0.0), 50);
+    assert!(separation_force(pos, crowd).length() <= 1.0 + f32::EPSILON);
+}
+
+#[test]
+fn lod_has_a_dead_zone() {
+    let between = (LOD_NEAR + LOD_FAR) / 2.0;
+    // Somewhere in between, an enemy stays however it already is
+    assert!(!should_simplify(false, Some(between)));
+    assert!(should_simplify(true, Some(between)));
+
+    assert!(should_simplify(false, Some(LOD_FAR + 1.0)));
+    assert!(!should_simplify(true, Some(LOD_NEAR - 1.0)));
+    assert!(should_simplify(false, None));
+}
+
+#[test]
+fn enemies_on_screen_keep_their_colliders() {
+    let mut app = App::new();
+    app.add_systems(Update, update_enemy_lod::<(), ()>);
+    app.world_mut().spawn((
+        Player {
+            client: PeerId::Local(0),
+        },
+        Position(Vec2::ZERO),
+    ));
+    let enemy = |pos: Vec2| {
+        (
+            Enemy {
+                kind: EnemyKind::default(),
+                state: EnemyState::LookForTargets,
+            },
+            Position(pos),
+        )
+    };
+    let corner = app.world_mut().spawn(enemy(VIEW_EXTENT)).id();
+    let edge = app
+        .world_mut()
+        .spawn(enemy(Vec2::new(-VIEW_EXTENT.x, 0.0)))
+        .id();
+    // Even one that was simplified gets its collider back before it comes into view
+    let returning = app
+        .world_mut()
+        .spawn((
+            enemy(VIEW_EXTENT * 1.05),
+            SimplifiedPhysics,
+            ColliderDisabled,
+        ))
+        .id();
+    let off_screen = app
+        .world_mut()
+        .spawn(enemy(Vec2::new(LOD_FAR + 1.0, 0.0)))
+        .id();
+    app.update();
+
+    for ent in [corner, edge, returning] {
+        assert!(!app.world().entity(ent).contains::<ColliderDisabled>());
+    }
+    assert!(
+        app.world()
+            .entity(off_screen)
+            .contains::<ColliderDisabled>()
+    );
+}
+
+#[test]
+fn boss_phases_follow_health() {
+    let registry = EnemyRegistry::load();
+    let ogre = registry.get(registry.kind("ogre").expect("The ogre should exist"));
+    let boss = ogre.boss.as_ref().expect("The ogre should be a boss");
+
+    assert_eq!(boss.phase_for(1.0), 0);
+    assert_eq!(boss.phase_for(0.5), 1);
+    assert_eq!(boss.phase_for(0.1), 2);
+    assert_eq!(boss.behaviour(&ogre.behaviour, 0), &ogre.behaviour);
+    assert!(matches!(
+        boss.behaviour(&ogre.behaviour, 1),
+        EnemyBehaviour::Charger(_)
+    ));
+    assert!(boss.behaviour(&ogre.behaviour, 2).ranged_attack().is_some());
+}
+
+#[test]
+fn bosses_are_well_formed() {
+    let registry = EnemyRegistry::load();
+    for (_kind, def) in registry.iter() {
+        let Some(boss) = &def.boss else {
+            continue;
+        };
+        // The director would spawn them like any other enemy otherwise
+        assert_eq!(def.spawning.weight, 0.0, "{} can spawn normally", def.name);
+        assert!(boss.reward_xp > 0.0, "{} has no reward", def.name);
+        assert!(
+            boss.phases
+                .windows(2)
+                .all(|w| w[0].below_health > w[1].below_health),
+            "{} has phases out of order",
+            def.name
+        );
+    }
+}
+
+#[test]
+fn boss_schedule_only_has_bosses() {
+    let registry = EnemyRegistry::load();
+    let schedule = BossSchedule::import();
+    assert!(!schedule.encounters.is_empty());
+    for encounter in &schedule.encounters {
+        let kind = registry
+            .kind(&encounter.boss)
+            .unwrap_or_else(|| panic!("{} isn't an enemy", encounter.boss));
+        assert!(registry.get(kind).boss.is_some());
+    }
+}
@@ -0,0 +1,73 @@
+use rand::{SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::{
+    drops::PickupKind,
+    loot::*,
+    rng::{MatchRng, RngStream},
+};
+
+fn test_table() -> LootTable {
+    LootTable {
+        rolls: 1,
+        entries: vec![
+            LootEntry {
+                drop: LootDrop::Nothing,
+                weight: 1.0,
+                luck_scaling: -1.0,
+            },
+            LootEntry {
+                drop: LootDrop::Pickup(PickupKind::Magnet),
+                weight: 1.0,
+                luck_scaling: 1.0,
+            },
+        ],
+    }
+}
+
+#[test]
+fn loot_tables_load() {
+    let tables = LootTables::import();
+    for table in [&tables.enemy, &tables.elite, &tables.chest]
+        .into_iter()
+        .chain(tables.props.values())
+    {
+        assert!(table.rolls > 0);
+        assert!(table.entries.iter().all(|e| e.weight >= 0.0));
+    }
+}
+
+#[test]
+fn default_luck_leaves_weights_alone() {
+    for entry in test_table().entries {
+        assert_eq!(entry.weight_with(1.0), entry.weight);
+    }
+}
+
+#[test]
+fn luck_makes_good_drops_likelier() {
+    let table = test_table();
+    let drops_with = |luck: f32| {
+        let mut rng = StdRng::seed_from_u64(5);
+        (0..2000)
+            .filter(|_| !table.roll(luck, &mut rng).is_empty())
+            .count()
+    };
+    let unlucky = drops_with(0.5);
+    let normal = drops_with(1.0);
+    let lucky = drops_with(2.0);
+    assert!(unlucky < normal && normal < lucky);
+    // At luck 2 the odds are 4 to 1, so about 80% of rolls should drop
+    assert!((1500..1700).contains(&lucky), "{lucky} of 2000 dropped");
+}
+
+#[test]
+fn same_seed_same_loot() {
+    let tables = LootTables::import();
+    let roll_all = |seed: u64| {
+        let mut rng = MatchRng::new(seed);
+        (0..50)
+            .flat_map(|_| tables.chest.roll(1.5, rng.stream(RngStream::Loot)))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(roll_all(42), roll_all(42));
+    assert_ne!(roll_all(42), roll_all(43));
+}
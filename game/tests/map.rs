@@ -0,0 +1,80 @@
+use bevy::math::Vec2;
+use rand::{SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::{game_rules::MapKind, map::*};
+
+#[test]
+fn the_greens_loads() {
+    let map = MapDefinition::import(MapKind::TheGreens);
+    assert!(!map.obstacles.is_empty());
+    assert!(map.bounds.contains(map.player_start.center));
+
+    // Nobody should start the match stuck in a wall
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let pos = map.player_start.random_point(&mut rng);
+        assert!(map.bounds.contains(pos));
+        assert!(!map.in_obstacle(pos), "Players can start in an obstacle");
+    }
+}
+
+#[test]
+fn spawn_zones_are_polygons() {
+    let triangle = SpawnZone {
+        points: vec![Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(0.0, 100.0)],
+    };
+    assert!(triangle.contains(Vec2::new(10.0, 10.0)));
+    assert!(!triangle.contains(Vec2::new(60.0, 60.0)));
+    assert!(!triangle.contains(Vec2::new(-10.0, 10.0)));
+}
+
+#[test]
+fn spawns_avoid_obstacles() {
+    let map = MapDefinition::import(MapKind::TheGreens);
+    let obstacle = map.obstacles[0];
+    assert!(!map.is_valid_spawn(obstacle.center));
+    assert!(!map.is_valid_spawn(map.bounds.max + Vec2::ONE));
+
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..100 {
+        if let Some(pos) = map.spawn_point_near(obstacle.center, 300.0, 500.0, &mut rng) {
+            assert!(map.is_valid_spawn(pos));
+        }
+    }
+}
+
+#[test]
+fn clamp_keeps_things_in_bounds() {
+    let map = MapDefinition::import(MapKind::TheGreens);
+    let outside = map.bounds.max + Vec2::new(100.0, -100.0);
+    let clamped = map.clamp(outside);
+    assert_eq!(clamped.x, map.bounds.max.x);
+    assert_eq!(clamped.y, outside.y);
+    assert_eq!(map.clamp(Vec2::ZERO), Vec2::ZERO);
+}
+
+#[test]
+fn chunk_streaming_stays_flat() {
+    use bevy::math::Rect;
+    use snappa_survivors::render::map::*;
+
+    let view = Rect::from_center_size(Vec2::ZERO, Vec2::new(1920.0, 1080.0));
+    let near_origin = chunks_in_view(view, CHUNK_MARGIN, None);
+
+    // Looking somewhere far off needs exactly as many chunks as looking at the middle
+    let far = Rect::from_center_size(Vec2::splat(1_000_000.0), view.size());
+    assert_eq!(
+        chunks_in_view(far, CHUNK_MARGIN, None).len(),
+        near_origin.len()
+    );
+
+    // Every part of the view is covered
+    for corner in [view.min, view.max, Vec2::new(view.min.x, view.max.y)] {
+        assert!(near_origin.contains(&chunk_coord(corner)));
+    }
+
+    // Nothing gets loaded past the edge of a bounded map
+    let bounds = Rect::new(-CHUNK_SIZE, -CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE);
+    let bounded = chunks_in_view(view, CHUNK_MARGIN, Some(bounds));
+    assert_eq!(bounded.len(), 4);
+    assert!(chunks_in_view(far, CHUNK_MARGIN, Some(bounds)).is_empty());
+}
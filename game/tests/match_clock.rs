@@ -0,0 +1,64 @@
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use lightyear::prelude::PeerId;
+use snappa_survivors::shared::{
+    damage::Dead, game_rules::GameRules, match_clock::*, players::Player,
+};
+
+fn world_at(elapsed: f32) -> World {
+    let mut world = World::new();
+    world.insert_resource(GameRules {
+        match_length: 60.0,
+        ..default()
+    });
+    world.spawn(MatchClock { elapsed });
+    world
+}
+
+fn spawn_player(world: &mut World, id: u64) -> Entity {
+    world
+        .spawn(Player {
+            client: PeerId::Local(id),
+        })
+        .id()
+}
+
+fn check(world: &mut World) -> Option<MatchOutcome> {
+    world
+        .run_system_once(check_match_end::<(), ()>)
+        .expect("the check should run")
+}
+
+#[test]
+fn surviving_to_the_end_is_a_victory() {
+    let mut world = world_at(59.9);
+    spawn_player(&mut world, 0);
+    assert_eq!(check(&mut world), None);
+
+    let mut world = world_at(60.0);
+    spawn_player(&mut world, 0);
+    let fallen = spawn_player(&mut world, 1);
+    world.entity_mut(fallen).insert(Dead);
+    // One player making it is enough
+    assert_eq!(check(&mut world), Some(MatchOutcome::Victory));
+}
+
+#[test]
+fn everybody_dying_is_a_defeat() {
+    let mut world = world_at(10.0);
+    let first = spawn_player(&mut world, 0);
+    let second = spawn_player(&mut world, 1);
+    world.entity_mut(first).insert(Dead);
+    assert_eq!(check(&mut world), None);
+
+    world.entity_mut(second).insert(Dead);
+    assert_eq!(check(&mut world), Some(MatchOutcome::Defeat));
+}
+
+#[test]
+fn nothing_ends_without_a_clock() {
+    let mut world = World::new();
+    world.insert_resource(GameRules::default());
+    let player = spawn_player(&mut world, 0);
+    world.entity_mut(player).insert(Dead);
+    assert_eq!(check(&mut world), None);
+}
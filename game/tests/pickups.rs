@@ -0,0 +1,116 @@
+use avian2d::prelude::Position;
+use bevy::prelude::*;
+use lightyear::prelude::PeerId;
+use snappa_survivors::shared::{
+    colliders::CollisionEffect,
+    damage::DamageBuffer,
+    drops::{Pickup, PickupCollectedMessage, PickupKind},
+    enemies::{Enemy, EnemyKind, EnemyState},
+    pickups::*,
+    players::{Gold, Player},
+    stats::components::Health,
+};
+
+fn setup_app() -> App {
+    let mut app = App::new();
+    app.add_message::<PickupCollectedMessage>();
+    app
+}
+
+fn spawn_player(app: &mut App, pos: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            Player {
+                client: PeerId::Local(0),
+            },
+            Position(pos),
+            Health {
+                max: 100.0,
+                current: 50.0,
+            },
+        ))
+        .id()
+}
+
+fn spawn_pickup(app: &mut App, kind: PickupKind) -> Entity {
+    app.world_mut().spawn(Pickup { kind }).id()
+}
+
+fn spawn_enemy(app: &mut App, pos: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            Enemy {
+                kind: EnemyKind(0),
+                state: EnemyState::LookForTargets,
+            },
+            Position(pos),
+            DamageBuffer::default(),
+        ))
+        .id()
+}
+
+fn collect(app: &mut App, effect: impl CollisionEffect, player: Entity, pickup: Entity) {
+    let world = app.world_mut();
+    effect.apply_to(&mut world.commands(), player, pickup);
+    world.flush();
+}
+
+#[test]
+fn heals_cap_at_max_health() {
+    let mut app = setup_app();
+    let player = spawn_player(&mut app, Vec2::ZERO);
+    let pickup = spawn_pickup(&mut app, PickupKind::Heal { amount: 80.0 });
+    collect(&mut app, Heal { amount: 80.0 }, player, pickup);
+
+    let health = app.world().get::<Health>(player).unwrap();
+    assert_eq!(health.current, health.max);
+    assert!(app.world().get_entity(pickup).is_err());
+}
+
+#[test]
+fn pickups_only_get_taken_once() {
+    let mut app = setup_app();
+    let first = spawn_player(&mut app, Vec2::ZERO);
+    let second = spawn_player(&mut app, Vec2::ZERO);
+    let pickup = spawn_pickup(&mut app, PickupKind::Gold { amount: 5 });
+    // Both players touched it on the same tick
+    {
+        let world = app.world_mut();
+        let mut commands = world.commands();
+        CollectGold { amount: 5 }.apply_to(&mut commands, first, pickup);
+        CollectGold { amount: 5 }.apply_to(&mut commands, second, pickup);
+        world.flush();
+    }
+    assert_eq!(app.world().get::<Gold>(first).unwrap().0, 5);
+    assert_eq!(app.world().get::<Gold>(second).unwrap().0, 0);
+}
+
+#[test]
+fn bombs_only_hit_what_is_on_screen() {
+    let mut app = setup_app();
+    let player = spawn_player(&mut app, Vec2::ZERO);
+    let near = spawn_enemy(&mut app, Vec2::new(200.0, 100.0));
+    let far = spawn_enemy(&mut app, BOMB_EXTENT * 2.0);
+    let pickup = spawn_pickup(&mut app, PickupKind::Bomb { damage: 40.0 });
+    collect(&mut app, Bomb { damage: 40.0 }, player, pickup);
+
+    let hits = app.world().get::<DamageBuffer>(near).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].amount, 40.0);
+    assert!(app.world().get::<DamageBuffer>(far).unwrap().is_empty());
+}
+
+#[test]
+fn freezes_stack_to_the_longest() {
+    let mut app = setup_app();
+    let player = spawn_player(&mut app, Vec2::ZERO);
+    let enemy = spawn_enemy(&mut app, Vec2::new(5000.0, 0.0));
+
+    let long = spawn_pickup(&mut app, PickupKind::Freeze { duration: 5.0 });
+    collect(&mut app, Freeze { duration: 5.0 }, player, long);
+    let short = spawn_pickup(&mut app, PickupKind::Freeze { duration: 2.0 });
+    collect(&mut app, Freeze { duration: 2.0 }, player, short);
+
+    // However far away, every enemy gets frozen
+    assert_eq!(app.world().get::<Frozen>(enemy).unwrap().remaining, 5.0);
+}
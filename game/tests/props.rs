@@ -0,0 +1,42 @@
+use rand::{SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::{
+    game_rules::MapKind, loot::LootTables, map::MapDefinition, props::*,
+};
+
+#[test]
+fn props_always_drop_something() {
+    let tables = LootTables::import();
+    let mut rng = StdRng::seed_from_u64(3);
+    for kind in [PropKind::Cooler, PropKind::Table] {
+        assert!(kind.health() > 0.0);
+        let table = tables
+            .prop(kind)
+            .unwrap_or_else(|| panic!("{kind:?} has no loot table"));
+        for _ in 0..100 {
+            assert!(!table.roll(1.0, &mut rng).is_empty());
+        }
+    }
+}
+
+#[test]
+fn map_props_are_placed_sensibly() {
+    let map = MapDefinition::import(MapKind::TheGreens);
+    for prop in &map.props {
+        assert!(map.bounds.contains(prop.position));
+        assert!(
+            !map.in_obstacle(prop.position),
+            "{:?} is in a wall",
+            prop.kind
+        );
+        assert!(
+            prop.position.distance(map.player_start.center)
+                > map.player_start.radius + prop.kind.size().length(),
+            "{:?} is on top of the players",
+            prop.kind
+        );
+    }
+    if let Some(rules) = &map.prop_spawning {
+        assert!(rules.interval > 0.0);
+        assert!(!rules.kinds.is_empty());
+    }
+}
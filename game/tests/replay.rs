@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use snappa_survivors::{
+    build::build_replay_app,
+    client::replay::*,
+    shared::{
+        combat::CombatSystemSet,
+        game_rules::GameRules,
+        inputs::Movement,
+        lobby::ClientStartGameMessage,
+        players::Player,
+        states::{AppState, InGameState},
+    },
+};
+
+mod common;
+use common::{TempPath, move_to_single_player, setup_test_client, tick_app};
+
+fn rules() -> GameRules {
+    GameRules {
+        seed: 1234,
+        ..default()
+    }
+}
+
+#[test]
+fn held_inputs_run_together() {
+    let mut replay = ReplayFile::new(rules());
+    let ticks = [Vec2::X, Vec2::X, Vec2::X, Vec2::ZERO, Vec2::Y, Vec2::Y];
+    for movement in ticks {
+        replay.push_input(movement);
+    }
+    assert_eq!(replay.inputs.len(), 3);
+    assert_eq!(replay.ticks(), 6);
+    assert_eq!(replay.tick_inputs().collect::<Vec<_>>(), ticks);
+}
+
+#[test]
+fn replays_survive_a_round_trip() {
+    let mut replay = ReplayFile::new(rules());
+    for i in 0..100 {
+        replay.push_input(Vec2::from_angle(i as f32 / 10.0).round());
+    }
+    replay.end = Some(ReplaySnapshot {
+        ticks: replay.ticks(),
+        players: vec![PlayerSnapshot {
+            position: Vec2::new(12.5, -3.0),
+            health: 80.0,
+            gold: 7,
+        }],
+        enemies: 31,
+    });
+    let path = TempPath::new("replay_round_trip.ron");
+    replay.save(&path).expect("the replay should save");
+    let loaded = ReplayFile::load(&path);
+    assert_eq!(loaded.rules.seed, replay.rules.seed);
+    assert_eq!(loaded.inputs, replay.inputs);
+    assert_eq!(loaded.end, replay.end);
+}
+
+/// Walks in a slow circle, so that the recording has plenty of different inputs in it
+fn walk_in_circles(
+    mut ticks: Local<u32>,
+    mut q_movement: Query<&mut ActionValue, (With<Action<Movement>>, With<ActionOf<Player>>)>,
+) {
+    *ticks += 1;
+    let movement = Vec2::from_angle(*ticks as f32 / 40.0).round();
+    for mut value in &mut q_movement {
+        *value = movement.into();
+    }
+}
+
+#[test]
+fn playback_ends_where_the_recording_did() {
+    let path = TempPath::new("replay_end_to_end.ron");
+    let mut app = setup_test_client();
+    app.insert_resource(ReplayRecorder::new(path.to_path_buf()))
+        .add_systems(
+            FixedUpdate,
+            walk_in_circles
+                .in_set(CombatSystemSet::PreCombat)
+                .before(record_inputs),
+        );
+    move_to_single_player(&mut app);
+    for _ in 0..30 {
+        tick_app(&mut app, 1.0 / 64.0);
+    }
+    app.world_mut().write_message(ClientStartGameMessage);
+    for _ in 0..30 {
+        tick_app(&mut app, 1.0 / 64.0);
+    }
+    assert_eq!(
+        *app.world().resource::<State<AppState>>().get(),
+        AppState::InGame
+    );
+
+    // The frames don't line up with the ticks at all, the way that they wouldn't on a real
+    // machine, while playback gets exactly one tick per update
+    let frames = [1.0 / 30.0, 1.0 / 144.0, 1.0 / 45.0, 1.0 / 90.0, 1.0 / 20.0];
+    for frame in frames.iter().cycle().take(300) {
+        tick_app(&mut app, *frame);
+    }
+    // Pausing partway through can't lose any of the ticks that still ran around it
+    app.world_mut()
+        .resource_mut::<NextState<InGameState>>()
+        .set(InGameState::Paused);
+    for frame in frames.iter().cycle().take(50) {
+        tick_app(&mut app, *frame);
+    }
+    app.world_mut()
+        .resource_mut::<NextState<InGameState>>()
+        .set(InGameState::InGame);
+    for frame in frames.iter().cycle().take(300) {
+        tick_app(&mut app, *frame);
+    }
+    app.world_mut().write_message(AppExit::Success);
+    app.update();
+
+    let recorded = ReplayFile::load(&path);
+    assert!(recorded.ticks() > 500, "only {} ticks", recorded.ticks());
+    assert!(recorded.end.is_some());
+
+    let mut playback = App::new();
+    build_replay_app(&mut playback, &path, false);
+    let exit = (0..recorded.ticks() + 600).find_map(|_| {
+        playback.update();
+        playback.should_exit()
+    });
+    assert_eq!(
+        exit,
+        Some(AppExit::Success),
+        "playback should finish in the same state as the recording"
+    );
+}
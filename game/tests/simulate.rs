@@ -0,0 +1,36 @@
+use snappa_survivors::{
+    client::simulate::*,
+    shared::{
+        game_rules::Difficulty, match_clock::MatchOutcome, players::CharacterKind,
+        weapons::WeaponKind,
+    },
+};
+
+fn config(seed: u64) -> SimulationConfig {
+    SimulationConfig {
+        character: CharacterKind::Dewey,
+        weapon: WeaponKind::DiceGuard,
+        difficulty: Difficulty::Easy,
+        seed,
+        match_length: Some(5.0),
+    }
+}
+
+#[test]
+fn a_short_match_plays_out() {
+    let summary = simulate_match(config(7));
+    assert_eq!(summary.seed, 7);
+    assert!(summary.outcome.is_some(), "the match should have ended");
+    assert!(summary.survival_time > 0.0);
+    assert!(summary.level >= 1);
+    if summary.outcome == Some(MatchOutcome::Victory) {
+        assert!(summary.survival_time >= 5.0);
+    }
+}
+
+#[test]
+fn each_run_gets_the_next_seed() {
+    let summaries = run_simulations(config(40), 2, None);
+    let seeds: Vec<u64> = summaries.iter().map(|s| s.seed).collect();
+    assert_eq!(seeds, [40, 41]);
+}
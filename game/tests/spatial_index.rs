@@ -0,0 +1,94 @@
+use bevy::{ecs::entity::Entity, math::Vec2};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use snappa_survivors::shared::{colliders::ColliderTypes, spatial_index::SpatialIndex};
+
+fn random_index(rng: &mut StdRng, n: u32) -> (SpatialIndex, Vec<(Entity, Vec2, bool)>) {
+    let mut index = SpatialIndex::default();
+    let mut all = Vec::new();
+    for i in 0..n {
+        let ent = Entity::from_raw_u32(i + 1).unwrap();
+        let pos = Vec2::new(
+            rng.random_range(-1000.0..1000.0),
+            rng.random_range(-1000.0..1000.0),
+        );
+        let is_enemy = rng.random_bool(0.8);
+        let layer = if is_enemy {
+            ColliderTypes::Enemy
+        } else {
+            ColliderTypes::Player
+        };
+        index.insert(ent, pos, layer);
+        all.push((ent, pos, is_enemy));
+    }
+    (index, all)
+}
+
+#[test]
+fn nearest_matches_a_full_scan() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let (index, all) = random_index(&mut rng, 500);
+    for _ in 0..50 {
+        let q = Vec2::new(
+            rng.random_range(-1500.0..1500.0),
+            rng.random_range(-1500.0..1500.0),
+        );
+        let mut enemies: Vec<_> = all.iter().filter(|(_, _, e)| *e).collect();
+        enemies.sort_by(|a, b| a.1.distance(q).total_cmp(&b.1.distance(q)));
+
+        let nearest = index.nearest(q, ColliderTypes::Enemy).unwrap();
+        assert_eq!(nearest.entity, enemies[0].0);
+
+        let k: Vec<Entity> = index
+            .k_nearest(q, 5, ColliderTypes::Enemy)
+            .into_iter()
+            .map(|e| e.entity)
+            .collect();
+        let expected: Vec<Entity> = enemies.iter().take(5).map(|e| e.0).collect();
+        assert_eq!(k, expected);
+    }
+}
+
+#[test]
+fn within_radius_matches_a_full_scan() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let (index, all) = random_index(&mut rng, 500);
+    let q = Vec2::new(100.0, -50.0);
+    let mut found: Vec<Entity> = index
+        .within_radius(q, 300.0, ColliderTypes::Enemy)
+        .map(|e| e.entity)
+        .collect();
+    let mut expected: Vec<Entity> = all
+        .iter()
+        .filter(|(_, pos, e)| *e && pos.distance(q) <= 300.0)
+        .map(|e| e.0)
+        .collect();
+    found.sort();
+    expected.sort();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn densest_cell_finds_the_crowd() {
+    let mut index = SpatialIndex::new(100.0);
+    for i in 0..10 {
+        let ent = Entity::from_raw_u32(i + 1).unwrap();
+        index.insert(
+            ent,
+            Vec2::new(510.0 + i as f32, 520.0),
+            ColliderTypes::Enemy,
+        );
+    }
+    index.insert(
+        Entity::from_raw_u32(100).unwrap(),
+        Vec2::new(-300.0, 0.0),
+        ColliderTypes::Enemy,
+    );
+    let (center, count) = index.densest_cell(ColliderTypes::Enemy).unwrap();
+    assert_eq!(count, 10);
+    assert_eq!(center, Vec2::new(550.0, 550.0));
+    assert!(index.densest_cell(ColliderTypes::Player).is_none());
+
+    index.clear();
+    assert!(index.is_empty());
+    assert!(index.nearest(Vec2::ZERO, ColliderTypes::Enemy).is_none());
+}
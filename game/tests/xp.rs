@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use lightyear::prelude::PeerId;
+use snappa_survivors::shared::{
+    game_rules::{GameRules, XPSharing},
+    players::Player,
+    stats::{components::XPGain, xp::*},
+};
+
+fn setup_xp_app(xp_sharing: XPSharing) -> App {
+    let mut app = App::new();
+    app.add_message::<ApplyXPMessage>()
+        .add_message::<LevelUpMessage>()
+        .insert_resource(GameRules {
+            xp_sharing,
+            ..default()
+        })
+        .insert_resource(test_curve())
+        .add_systems(Update, add_xp::<()>);
+    app
+}
+
+fn test_curve() -> LevelCurve {
+    LevelCurve {
+        thresholds: vec![5.0, 10.0, 20.0],
+        step_past_table: 10.0,
+    }
+}
+
+fn spawn_player(app: &mut App, id: u64) -> Entity {
+    app.world_mut()
+        .spawn((
+            Player {
+                client: PeerId::Local(id),
+            },
+            LevelManager::default(),
+        ))
+        .id()
+}
+
+#[test]
+fn per_player_xp_goes_to_collector() {
+    let mut app = setup_xp_app(XPSharing::PerPlayer);
+    let collector = spawn_player(&mut app, 0);
+    let other = spawn_player(&mut app, 1);
+
+    app.world_mut().write_message(ApplyXPMessage {
+        collector,
+        amount: 3.0,
+    });
+    app.update();
+
+    assert_eq!(
+        app.world().get::<LevelManager>(collector).unwrap().c_xp,
+        3.0
+    );
+    assert_eq!(app.world().get::<LevelManager>(other).unwrap().c_xp, 0.0);
+}
+
+#[test]
+fn shared_xp_goes_to_party() {
+    let mut app = setup_xp_app(XPSharing::Shared);
+    let party = app.world_mut().spawn(LevelManager::default()).id();
+    let collector = app
+        .world_mut()
+        .spawn(Player {
+            client: PeerId::Local(0),
+        })
+        .id();
+
+    app.world_mut().write_message(ApplyXPMessage {
+        collector,
+        amount: 3.0,
+    });
+    app.update();
+
+    assert_eq!(app.world().get::<LevelManager>(party).unwrap().c_xp, 3.0);
+}
+
+#[test]
+fn level_curve_extends_past_table() {
+    let curve = test_curve();
+    assert_eq!(curve.xp_to_reach(1), 0.0);
+    assert_eq!(curve.xp_to_reach(2), 5.0);
+    assert_eq!(curve.xp_to_reach(4), 20.0);
+    assert_eq!(curve.xp_to_reach(5), 30.0);
+    assert_eq!(curve.xp_to_reach(7), 50.0);
+}
+
+#[test]
+fn level_curve_asset_is_increasing() {
+    let curve = LevelCurve::import();
+    assert!(curve.step_past_table > 0.0);
+    assert!(curve.thresholds.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn big_xp_crosses_several_levels() {
+    let curve = test_curve();
+    let mut level = LevelManager::new(&curve);
+    let reached = level.add_xp(25.0, &curve);
+
+    assert_eq!(reached, vec![2, 3, 4]);
+    assert_eq!(level.c_level, 4);
+    assert_eq!(level.prev_max, 20.0);
+    assert_eq!(level.next_max, 30.0);
+}
+
+#[test]
+fn xp_gain_scales_and_writes_level_ups() {
+    let mut app = setup_xp_app(XPSharing::PerPlayer);
+    let collector = spawn_player(&mut app, 0);
+    app.world_mut().entity_mut(collector).insert(XPGain(2.0));
+
+    app.world_mut().write_message(ApplyXPMessage {
+        collector,
+        amount: 5.0,
+    });
+    app.update();
+
+    let level = app.world().get::<LevelManager>(collector).unwrap();
+    assert_eq!(level.c_xp, 10.0);
+    assert_eq!(level.c_level, 3);
+
+    let level_ups: Vec<LevelUpMessage> = app
+        .world_mut()
+        .resource_mut::<Messages<LevelUpMessage>>()
+        .drain()
+        .collect();
+    assert_eq!(
+        level_ups,
+        vec![
+            LevelUpMessage {
+                level_manager: collector,
+                level: 2
+            },
+            LevelUpMessage {
+                level_manager: collector,
+                level: 3
+            },
+        ]
+    );
+}